@@ -0,0 +1,292 @@
+// Adjacency constraints: rules for the values of two adjacent squares, marked on the edge between them
+// - Kropki Sudoku: a white dot means the values are consecutive, a black dot means one value is double the other
+// - XV Sudoku: an X means the values add up to 10, a V means they add up to 5
+//
+// Puzzles often add the "negative constraint": when there is no mark between two adjacent squares, none of the
+// listed rules holds for them. E.g. in a Kropki Sudoku without a dot between two squares, they can't be 3 and 4
+//
+// A value is kept if the other square still has a different value that satisfies the rule together with it
+
+use crate::constraint::Constraint;
+use crate::layout::Layout;
+use crate::sudoku::Board;
+use std::collections::HashSet;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EdgeRule {
+    // White Kropki dot
+    Consecutive,
+    // Black Kropki dot
+    Double,
+    // X (10) or V (5)
+    Sum(usize),
+}
+
+impl EdgeRule {
+    pub fn allows(&self, value: usize, other: usize) -> bool {
+        match self {
+            EdgeRule::Consecutive => value.abs_diff(other) == 1,
+            EdgeRule::Double => value == 2 * other || other == 2 * value,
+            EdgeRule::Sum(sum) => value + other == *sum,
+        }
+    }
+
+    // Names used in puzzle files: white, black, X and V
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "white" => Ok(EdgeRule::Consecutive),
+            "black" => Ok(EdgeRule::Double),
+            "x" => Ok(EdgeRule::Sum(10)),
+            "v" => Ok(EdgeRule::Sum(5)),
+            _ => Err(format!("Unknown edge rule '{}'", name)),
+        }
+    }
+}
+
+// A rule between two adjacent squares
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Edge {
+    pub first: usize,
+    pub second: usize,
+    pub rule: EdgeRule,
+}
+
+pub struct Adjacency {
+    edges: Vec<Edge>,
+    negative: Vec<EdgeRule>,
+    // Adjacent squares without an edge, only needed for the negative constraint
+    unmarked: Vec<(usize, usize)>,
+}
+
+impl Adjacency {
+    // The negative rules must not hold between adjacent squares without an edge. Leave empty if there is no negative constraint
+    pub fn new(edges: Vec<Edge>, negative: Vec<EdgeRule>, layout: &Layout) -> Self {
+        let mut unmarked = Vec::new();
+        if !negative.is_empty() {
+            let marked: HashSet<(usize, usize)> =
+                edges.iter().map(|edge| (edge.first.min(edge.second), edge.first.max(edge.second))).collect();
+            let size = layout.size();
+            for pos in 0..layout.cells() {
+                // Only look right and down, so every pair of neighbours is visited once
+                let right = Some(pos + 1).filter(|other| other % size != 0);
+                let down = Some(pos + size).filter(|other| *other < layout.cells());
+                for other in right.into_iter().chain(down) {
+                    if !marked.contains(&(pos, other)) {
+                        unmarked.push((pos, other));
+                    }
+                }
+            }
+        }
+        Self {
+            edges,
+            negative,
+            unmarked,
+        }
+    }
+
+    // Parses edges, one per line: two adjacent squares followed by the rule. An optional line starting
+    // with "negative" lists the rules that don't hold between adjacent squares without an edge. E.g.
+    // r1c1 r1c2 white
+    // r1c2 r2c2 black
+    // negative white black
+    // Empty lines are skipped
+    pub fn parse(edges: &str, layout: &Layout) -> Result<Self, String> {
+        let mut result = Vec::new();
+        let mut negative = Vec::new();
+        for line in edges.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words[0].eq_ignore_ascii_case("negative") {
+                for name in words[1..].iter() {
+                    negative.push(EdgeRule::parse(name)?);
+                }
+                continue;
+            }
+            if words.len() != 3 {
+                return Err(format!("Edge '{}' needs two squares and a rule", line));
+            }
+            let (first, second) = (layout.parse_square(words[0])?, layout.parse_square(words[1])?);
+            if !layout.adjacent(first, second) {
+                return Err(format!("Edge '{}' is not between adjacent squares", line));
+            }
+            result.push(Edge {
+                first,
+                second,
+                rule: EdgeRule::parse(words[2])?,
+            });
+        }
+        Ok(Adjacency::new(result, negative, layout))
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    pub fn negative(&self) -> &[EdgeRule] {
+        &self.negative
+    }
+
+    fn unmarked_allows(&self, value: usize, other: usize) -> bool {
+        !self.negative.iter().any(|rule| rule.allows(value, other))
+    }
+}
+
+impl Constraint for Adjacency {
+    fn prune(&self, board: &mut Board) -> bool {
+        let mut pruned = false;
+        for edge in self.edges.iter() {
+            let allows = |value, other| edge.rule.allows(value, other);
+            pruned |= prune_pair(board, edge.first, edge.second, allows);
+            pruned |= prune_pair(board, edge.second, edge.first, allows);
+        }
+        for (pos, other) in self.unmarked.iter() {
+            let allows = |value, other| self.unmarked_allows(value, other);
+            pruned |= prune_pair(board, *pos, *other, allows);
+            pruned |= prune_pair(board, *other, *pos, allows);
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        let value = |pos: usize| board.square_at(pos).value();
+        self.edges
+            .iter()
+            .all(|edge| edge.rule.allows(value(edge.first), value(edge.second)))
+            && self
+                .unmarked
+                .iter()
+                .all(|(pos, other)| self.unmarked_allows(value(*pos), value(*other)))
+    }
+}
+
+// Removes the values of a square that have no different partner value in the other square
+pub(crate) fn prune_pair(board: &mut Board, pos: usize, other: usize, allows: impl Fn(usize, usize) -> bool) -> bool {
+    let partners = board.square_at(other).candidates();
+    let mut pruned = false;
+    for value in board.square_at(pos).candidates() {
+        if !partners.iter().any(|partner| *partner != value && allows(value, *partner)) {
+            pruned |= board.eliminate(pos, value);
+        }
+    }
+    pruned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::*;
+
+    #[test]
+    fn test_edge_rules() {
+        assert!(EdgeRule::Consecutive.allows(4, 3));
+        assert!(!EdgeRule::Consecutive.allows(4, 2));
+        assert!(EdgeRule::Double.allows(3, 6));
+        assert!(EdgeRule::Double.allows(8, 4));
+        assert!(!EdgeRule::Double.allows(3, 5));
+        assert!(EdgeRule::Sum(10).allows(3, 7));
+        assert!(!EdgeRule::Sum(5).allows(3, 7));
+    }
+
+    #[test]
+    fn test_parse_edges() {
+        let layout = Layout::classic();
+        let adjacency = Adjacency::parse("\nr1c1 r1c2 white\nr1c2 r2c2 Black\n\nr5c5 r5c6 X\nnegative white black\n", &layout).unwrap();
+        assert_eq!(
+            &[
+                Edge { first: 0, second: 1, rule: EdgeRule::Consecutive },
+                Edge { first: 1, second: 10, rule: EdgeRule::Double },
+                Edge { first: 40, second: 41, rule: EdgeRule::Sum(10) },
+            ],
+            adjacency.edges()
+        );
+        assert_eq!(&[EdgeRule::Consecutive, EdgeRule::Double], adjacency.negative());
+        // 2 * 9 * 8 pairs of adjacent squares, minus the 3 edges
+        assert_eq!(141, adjacency.unmarked.len());
+    }
+
+    #[test]
+    fn test_unmarked_neighbours() {
+        let layout = Layout::new(2, 2);
+        let adjacency = Adjacency::parse("r1c2 r1c1 X
+r2c2 r3c2 V
+Negative X V", &layout).unwrap();
+        assert_eq!(&[EdgeRule::Sum(10), EdgeRule::Sum(5)], adjacency.negative());
+        let mut expected = Vec::new();
+        for pos in 0..layout.cells() {
+            for other in pos + 1..layout.cells() {
+                if layout.adjacent(pos, other) && (pos, other) != (0, 1) && (pos, other) != (5, 9) {
+                    expected.push((pos, other));
+                }
+            }
+        }
+        assert_eq!(22, expected.len());
+        assert_eq!(expected, adjacency.unmarked);
+    }
+
+    #[test]
+    fn test_invalid_edges_are_rejected() {
+        let layout = Layout::new(2, 2);
+        let error = |edges| Adjacency::parse(edges, &layout).map(|_| ()).unwrap_err();
+        assert_eq!("Edge 'r1c1 r1c2' needs two squares and a rule", error("r1c1 r1c2"));
+        assert_eq!("Edge 'r1c1 r2c2 V' is not between adjacent squares", error("r1c1 r2c2 V"));
+        assert_eq!("Unknown edge rule 'grey'", error("r1c1 r1c2 grey"));
+        assert_eq!("Unknown edge rule 'Y'", error("negative X Y"));
+        assert_eq!("Square 'r5c1' is outside the grid", error("r5c1 r4c1 X"));
+    }
+
+    #[test]
+    fn test_edges_prune_values_without_partner() {
+        let layout = Layout::classic();
+        let adjacency = Adjacency::parse("r1c1 r1c2 black\nr2c1 r2c2 V", &layout).unwrap();
+        let mut board = Board::new("edges", "");
+        assert!(adjacency.prune(&mut board));
+        assert_eq!(vec![1, 2, 3, 4, 6, 8], board.square_at(0).candidates());
+        assert_eq!(vec![1, 2, 3, 4], board.square_at(9).candidates());
+
+        let mut board = Board::new("edges", "3........");
+        adjacency.prune(&mut board);
+        assert_eq!(vec![6], board.square_at(1).candidates());
+    }
+
+    #[test]
+    fn test_negative_constraint_prunes_unmarked_neighbours() {
+        let layout = Layout::classic();
+        let adjacency = Adjacency::parse("negative X V", &layout).unwrap();
+        let mut board = Board::new("negative", "3........");
+        assert!(adjacency.prune(&mut board));
+        // 3 + 2 = 5 and 3 + 7 = 10
+        assert_eq!(vec![1, 4, 5, 6, 8, 9], board.square_at(1).candidates());
+        assert_eq!(vec![1, 4, 5, 6, 8, 9], board.square_at(9).candidates());
+        assert_eq!(9, board.square_at(10).possibilities());
+    }
+
+    // All Kropki dots of a solved board, a white dot is used if both dots are possible
+    fn kropki_edges(board: &Board) -> Vec<Edge> {
+        let layout = board.layout();
+        let mut edges = Vec::new();
+        for first in 0..layout.cells() {
+            for second in first + 1..layout.cells() {
+                let (value, other) = (board.square_at(first).value(), board.square_at(second).value());
+                let rule = [EdgeRule::Consecutive, EdgeRule::Double].iter().copied().find(|rule| rule.allows(value, other));
+                if let (true, Some(rule)) = (layout.adjacent(first, second), rule) {
+                    edges.push(Edge { first, second, rule });
+                }
+            }
+        }
+        edges
+    }
+
+    #[test]
+    fn test_is_satisfied_checks_edges_and_unmarked_neighbours() {
+        let board = Board::new("solution", easy_sudoku_solution());
+        let negative = vec![EdgeRule::Consecutive, EdgeRule::Double];
+        let edges = kropki_edges(&board);
+        assert!(Adjacency::new(edges.clone(), negative.clone(), board.layout()).is_satisfied(&board));
+
+        // Without the negative constraint, missing dots are fine
+        assert!(Adjacency::new(edges[1..].to_vec(), Vec::new(), board.layout()).is_satisfied(&board));
+        assert!(!Adjacency::new(edges[1..].to_vec(), negative, board.layout()).is_satisfied(&board));
+
+        let wrong = Edge { rule: EdgeRule::Sum(10), ..edges[0] };
+        assert!(!Adjacency::new(vec![wrong], Vec::new(), board.layout()).is_satisfied(&board));
+    }
+}
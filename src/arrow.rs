@@ -0,0 +1,145 @@
+// Arrow Sudoku: the values along an arrow add up to the value in the circle at the start of the arrow
+// Values on an arrow may repeat, unless the squares are peers
+//
+// The bounds of the circle and the arrow limit each other:
+// - The circle lies between the lowest and the highest possible sum of the arrow
+// - Each square on the arrow lies between the lowest circle minus the highest sum of the other squares on the arrow,
+//   and the highest circle minus the lowest sum of the other squares on the arrow
+// Example: a circle with an arrow of 2 squares in a 9x9 grid can only contain 2..9
+
+use crate::constraint::Constraint;
+use crate::layout::Layout;
+use crate::sudoku::Board;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Arrow {
+    circle: usize,
+    squares: Vec<usize>,
+}
+
+impl Arrow {
+    pub fn new(circle: usize, squares: Vec<usize>) -> Self {
+        Self { circle, squares }
+    }
+
+    // Parses arrows, one per line: the circle, followed by a colon and the squares of the arrow. E.g.
+    // r1c1: r1c2 r1c3
+    // r5c5: r6c6 r7c7 r8c8
+    // Empty lines are skipped
+    pub fn parse(arrows: &str, layout: &Layout) -> Result<Vec<Self>, String> {
+        let mut result = Vec::new();
+        for line in arrows.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let (circle, squares) = match line.split_once(':') {
+                Some((circle, squares)) => (layout.parse_square(circle.trim())?, layout.parse_squares(squares)?),
+                None => return Err(format!("Arrow '{}' has no circle", line)),
+            };
+            if squares.is_empty() || squares.len() >= layout.size() {
+                return Err(format!("Arrow '{}' needs 1 to {} squares", line, layout.size() - 1));
+            }
+            let mut path = vec![circle];
+            path.extend(squares.iter());
+            if let Some((pos, other)) = layout.gap_in(&path) {
+                return Err(format!(
+                    "Arrow '{}' has a gap between {} and {}",
+                    line,
+                    layout.name_of(pos),
+                    layout.name_of(other)
+                ));
+            }
+            result.push(Arrow::new(circle, squares));
+        }
+        Ok(result)
+    }
+
+    pub fn circle(&self) -> usize {
+        self.circle
+    }
+
+    // The squares of the arrow, starting next to the circle
+    pub fn squares(&self) -> &[usize] {
+        &self.squares
+    }
+}
+
+impl Constraint for Arrow {
+    fn prune(&self, board: &mut Board) -> bool {
+        let size = board.size();
+        let lowest: Vec<usize> = self.squares.iter().map(|pos| board.square_at(*pos).value()).collect();
+        let highest: Vec<usize> = self.squares.iter().map(|pos| board.square_at(*pos).highest_value()).collect();
+        let lowest_sum: usize = lowest.iter().sum();
+        let highest_sum: usize = highest.iter().sum();
+
+        let mut pruned = board.eliminate_outside(self.circle, lowest_sum, highest_sum);
+        let circle = board.square_at(self.circle);
+        let (lowest_circle, highest_circle) = (circle.value(), circle.highest_value());
+        for (index, pos) in self.squares.iter().enumerate() {
+            let others_lowest = lowest_sum - lowest[index];
+            let others_highest = highest_sum - highest[index];
+            let low = lowest_circle.saturating_sub(others_highest).max(1);
+            let high = highest_circle.saturating_sub(others_lowest).min(size);
+            pruned |= board.eliminate_outside(*pos, low, high);
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        let sum: usize = self.squares.iter().map(|pos| board.square_at(*pos).value()).sum();
+        board.square_at(self.circle).value() == sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_arrows() {
+        let layout = Layout::classic();
+        let arrows = Arrow::parse("\nr1c1: r1c2 r2c3\n\nr9c9:r8c8\n", &layout).unwrap();
+        assert_eq!(vec![Arrow::new(0, vec![1, 11]), Arrow::new(80, vec![70])], arrows);
+    }
+
+    #[test]
+    fn test_invalid_arrows_are_rejected() {
+        let layout = Layout::new(2, 2);
+        assert_eq!(
+            Err("Arrow 'r1c1 r1c2' has no circle".to_string()),
+            Arrow::parse("r1c1 r1c2", &layout)
+        );
+        assert_eq!(
+            Err("Arrow 'r1c1:' needs 1 to 3 squares".to_string()),
+            Arrow::parse("r1c1:", &layout)
+        );
+        assert_eq!(
+            Err("Arrow 'r1c1: r2c2 r2c4' has a gap between r2c2 and r2c4".to_string()),
+            Arrow::parse("r1c1: r2c2 r2c4", &layout)
+        );
+        assert_eq!(Err("Invalid square 'c1'".to_string()), Arrow::parse("c1: r1c2", &layout));
+    }
+
+    #[test]
+    fn test_circle_and_arrow_limit_each_other() {
+        let arrow = Arrow::new(0, vec![1, 2]);
+        let mut board = Board::new("arrow", "");
+        assert!(arrow.prune(&mut board));
+        assert_eq!(vec![2, 3, 4, 5, 6, 7, 8, 9], board.square_at(0).candidates());
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], board.square_at(1).candidates());
+        assert!(!arrow.prune(&mut board));
+
+        // A small circle limits the arrow, a large arrow square limits the circle
+        let mut board = Board::new("arrow", "4........");
+        arrow.prune(&mut board);
+        assert_eq!(vec![1, 2, 3], board.square_at(1).candidates());
+        assert_eq!(vec![1, 2, 3], board.square_at(2).candidates());
+        let mut board = Board::new("arrow", ".7.......");
+        arrow.prune(&mut board);
+        assert_eq!(vec![8, 9], board.square_at(0).candidates());
+    }
+
+    #[test]
+    fn test_is_satisfied_requires_the_sum_in_the_circle() {
+        let arrow = Arrow::new(0, vec![1, 2]);
+        assert!(arrow.is_satisfied(&Board::new("ok", "936......")));
+        assert!(!arrow.is_satisfied(&Board::new("wrong", "836......")));
+    }
+}
@@ -0,0 +1,144 @@
+// A constraint is a rule for the values of a board. E.g. the cages of a Killer Sudoku: the values in a cage must add
+// up to the cage's sum
+//
+// The classic rules are constraints too: every row, column and subgrid (and every extra unit or pair of extra peers
+// of the layout) contains distinct values. A board starts with these default constraints, and any combination of
+// extra constraints can be added to it
+//
+// Constraints are attached to a board and shared by all experimental boards cloned from it
+// While solving, the board asks every extra constraint to prune the possible values of the squares (its techniques
+// already enforce the default constraints), until no more progress is made. The board checks the known values against every constraint to find contradictions
+// early, and when all values are known, it checks if every constraint is satisfied
+
+use crate::layout::Layout;
+use crate::sudoku::Board;
+use std::rc::Rc;
+
+pub trait Constraint {
+    // Removes possible values that can't satisfy the constraint. Returns true if any value was removed
+    fn prune(&self, board: &mut Board) -> bool;
+
+    // Checks the known values of a board that may still have unknown squares. Returns false if they already
+    // break the constraint. By default, only complete boards are checked, with is_satisfied
+    fn is_consistent(&self, _board: &Board) -> bool {
+        true
+    }
+
+    // Checks the constraint on a board where every square has a known value
+    fn is_satisfied(&self, board: &Board) -> bool;
+
+    // The same constraint on a rearranged board, where square pos moved to positions[pos] and every value v became
+    // values[v - 1], see Transformation. None if the constraint can't be moved along, which is the default
+    fn rearranged(&self, _positions: &[usize], _values: &[usize]) -> Option<Rc<dyn Constraint>> {
+        None
+    }
+}
+
+// The squares contain distinct values. A group of as many squares as there are values contains every value once
+pub struct Distinct {
+    squares: Vec<usize>,
+}
+
+impl Distinct {
+    pub fn new(squares: Vec<usize>) -> Self {
+        Self { squares }
+    }
+
+    pub fn squares(&self) -> &[usize] {
+        &self.squares
+    }
+}
+
+impl Constraint for Distinct {
+    // A known value is removed from the other squares. If every value must be used, a value that is possible in only
+    // one square becomes the value of that square
+    fn prune(&self, board: &mut Board) -> bool {
+        let mut pruned = false;
+        for pos in self.squares.iter() {
+            let square = board.square_at(*pos);
+            if square.has_known_value() {
+                for other in self.squares.iter().filter(|other| *other != pos) {
+                    pruned |= board.eliminate(*other, square.value());
+                }
+            }
+        }
+        if self.squares.len() == board.size() {
+            for value in 1..=board.size() {
+                let mut possible = self.squares.iter().filter(|pos| board.square_at(**pos).can_have_value(value));
+                if let (Some(pos), None) = (possible.next(), possible.next()) {
+                    pruned |= board.eliminate_outside(*pos, value, value);
+                }
+            }
+        }
+        pruned
+    }
+
+    fn is_consistent(&self, board: &Board) -> bool {
+        // One bit per value, at most 25 values
+        let mut seen = 0u32;
+        for square in self.squares.iter().map(|pos| board.square_at(*pos)).filter(|square| square.has_known_value()) {
+            let bit = 1 << square.value();
+            if seen & bit != 0 {
+                return false;
+            }
+            seen |= bit;
+        }
+        true
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.is_consistent(board)
+    }
+
+    // Distinct values stay distinct after relabelling, so only the squares move
+    fn rearranged(&self, positions: &[usize], _values: &[usize]) -> Option<Rc<dyn Constraint>> {
+        Some(Rc::new(Distinct::new(self.squares.iter().map(|pos| positions[*pos]).collect())))
+    }
+}
+
+// The classic rules of a layout: distinct values in every unit, and in every pair of extra peers
+pub fn default_constraints(layout: &Layout) -> Vec<Rc<dyn Constraint>> {
+    let mut result: Vec<Rc<dyn Constraint>> = Vec::new();
+    for unit in layout.units().iter() {
+        result.push(Rc::new(Distinct::new(unit.clone())));
+    }
+    for (pos, other) in layout.extra_peers().iter() {
+        result.push(Rc::new(Distinct::new(vec![*pos, *other])));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_constraints_follow_the_layout() {
+        assert_eq!(27, default_constraints(&Layout::classic()).len());
+        let mut layout = Layout::classic();
+        layout.add_diagonals();
+        layout.add_peers(&[(0, 80)]);
+        assert_eq!(30, default_constraints(&layout).len());
+    }
+
+    #[test]
+    fn test_distinct_removes_known_values_and_promotes_singletons() {
+        let distinct = Distinct::new((0..9).collect());
+        let mut board = Board::new("distinct", "1234567..");
+        assert!(distinct.prune(&mut board));
+        assert_eq!(vec![8, 9], board.square_at(7).candidates());
+
+        // Only the last square can still be 9
+        board.eliminate(7, 9);
+        distinct.prune(&mut board);
+        assert_eq!(vec![9], board.square_at(8).candidates());
+        assert!(distinct.is_satisfied(&board));
+    }
+
+    #[test]
+    fn test_distinct_is_inconsistent_with_repeated_values() {
+        let distinct = Distinct::new(vec![0, 1, 9]);
+        assert!(distinct.is_consistent(&Board::new("ok", "12.......\n3........")));
+        assert!(!distinct.is_consistent(&Board::new("repeated", "1........\n1........")));
+    }
+}
@@ -0,0 +1,249 @@
+// Rates how difficult a puzzle is, based on the solving techniques the solver needs
+//
+// The puzzle is solved while an observer records every step:
+// - Propagation: removing known values from the possibilities of their peers
+// - Singletons: a square is the only one in a unit that can hold a value
+// - Naked pairs: two squares in a unit can only hold the same two values
+// - Guessing: the techniques are stuck, so the solver has to try values
+// The rating solves with the techniques in order of difficulty: a harder technique is only used when the easier ones
+// are stuck, and only the guesses that are tried count. So the hardest technique that was used is the hardest one the
+// puzzle needs. It gives the difficulty tier, a weighted count of all steps gives a numeric score
+// The weights favour hard steps, e.g. a puzzle with 3 guesses scores higher than one with 3 naked pairs
+
+use crate::observer::{Observer, SolveEvent};
+use crate::sudoku::{Board, Game};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Technique {
+    Propagation,
+    Singletons,
+    NakedPairs,
+    Guessing,
+}
+
+impl Technique {
+    // Score of a single step with this technique
+    fn weight(self) -> usize {
+        match self {
+            Technique::Propagation => 1,
+            Technique::Singletons => 3,
+            Technique::NakedPairs => 10,
+            Technique::Guessing => 50,
+        }
+    }
+}
+
+// Difficulty tiers, each allowing one more technique than the tier before it
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub fn hardest_technique(self) -> Technique {
+        match self {
+            Difficulty::Easy => Technique::Propagation,
+            Difficulty::Medium => Technique::Singletons,
+            Difficulty::Hard => Technique::NakedPairs,
+            Difficulty::Expert => Technique::Guessing,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Rating {
+    // None if the solver didn't take any step, e.g. the puzzle was already solved
+    pub hardest: Option<Technique>,
+    pub score: usize,
+    pub guesses: usize,
+}
+
+impl Rating {
+    pub fn difficulty(&self) -> Difficulty {
+        match self.hardest {
+            None | Some(Technique::Propagation) => Difficulty::Easy,
+            Some(Technique::Singletons) => Difficulty::Medium,
+            Some(Technique::NakedPairs) => Difficulty::Hard,
+            Some(Technique::Guessing) => Difficulty::Expert,
+        }
+    }
+
+    fn used(&mut self, technique: Technique) {
+        self.hardest = self.hardest.max(Some(technique));
+        self.score += technique.weight();
+    }
+}
+
+impl Observer for Rating {
+    fn notify(&mut self, _board: &Board, event: &SolveEvent) {
+        match event {
+            SolveEvent::ValuePropagated { .. } => self.used(Technique::Propagation),
+            SolveEvent::SingletonPromoted { .. } => self.used(Technique::Singletons),
+            SolveEvent::NakedPairFound { .. } => self.used(Technique::NakedPairs),
+            SolveEvent::GuessMade { .. } => {
+                self.used(Technique::Guessing);
+                self.guesses += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+// Solves a copy of the board and rates the steps that were needed
+pub fn rate(board: &Board) -> Rating {
+    let rating = Rc::new(RefCell::new(Rating::default()));
+    let mut board = board.clone();
+    board.depth = 0;
+    board.observer(rating.clone());
+    Game::from_board(board).solve_with(solve_stepwise);
+    let result = rating.borrow().clone();
+    result
+}
+
+// Board::solve looks for naked pairs in every round, this only does when propagation and singletons are stuck
+fn solve_stepwise(board: &mut Board) -> bool {
+    let mut pairs = Vec::new();
+    loop {
+        board.propagate_all_known_values();
+        if board.solved() {
+            return true;
+        }
+        if board.contains_contradiction() {
+            return false;
+        }
+        let progress_made =
+            board.promote_singletons() || board.find_naked_pairs(&mut pairs) || board.apply_constraints();
+        if !progress_made {
+            return false;
+        }
+    }
+}
+
+// The difficulty a generated puzzle must have
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Target {
+    // The hardest technique needed is exactly the hardest technique of the tier
+    Tier(Difficulty),
+    // The score lies in lowest..=highest
+    Score(usize, usize),
+}
+
+impl Target {
+    // True if the rating is not above the target. Removing more givens can still make the puzzle harder
+    pub fn allows(&self, rating: &Rating) -> bool {
+        match self {
+            Target::Tier(difficulty) => rating.difficulty() <= *difficulty,
+            Target::Score(_, highest) => rating.score <= *highest,
+        }
+    }
+
+    pub fn accepts(&self, rating: &Rating) -> bool {
+        match self {
+            Target::Tier(difficulty) => rating.difficulty() == *difficulty,
+            Target::Score(lowest, highest) => *lowest <= rating.score && rating.score <= *highest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::*;
+
+    #[test]
+    fn test_rate_classic_puzzles() {
+        let easy = rate(&Board::new("easy", easy_sudoku()));
+        assert_eq!(Some(Technique::Propagation), easy.hardest);
+        assert_eq!(Difficulty::Easy, easy.difficulty());
+        assert_eq!(0, easy.guesses);
+
+        let intermediate = rate(&Board::new("intermediate", intermediate_sudoku1()));
+        assert_eq!(Difficulty::Expert, intermediate.difficulty());
+        assert_eq!(1, intermediate.guesses);
+
+        let not_fun = rate(&Board::new("not fun", not_fun_sudoku1()));
+        assert_eq!(Some(Technique::Guessing), not_fun.hardest);
+        assert_eq!(Difficulty::Expert, not_fun.difficulty());
+        assert_eq!(15, not_fun.guesses);
+        assert!(not_fun.score > easy.score);
+    }
+
+    #[test]
+    fn test_harder_techniques_are_only_used_when_needed() {
+        let medium = rate(&Board::new("medium", MEDIUM));
+        assert_eq!(Some(Technique::Singletons), medium.hardest);
+        assert_eq!(Difficulty::Medium, medium.difficulty());
+
+        // Board::solve keeps looking for naked pairs first, although the puzzle doesn't need them
+        let pairs = Rc::new(RefCell::new(0));
+        let counted = pairs.clone();
+        let mut board = Board::new("medium", MEDIUM);
+        board.observer(Rc::new(RefCell::new(move |_: &Board, event: &SolveEvent| {
+            if let SolveEvent::NakedPairFound { .. } = event {
+                *counted.borrow_mut() += 1;
+            }
+        })));
+        assert!(board.solve());
+        assert!(*pairs.borrow() > 0);
+
+        let hard = rate(&Board::new("hard", HARD));
+        assert_eq!(Some(Technique::NakedPairs), hard.hardest);
+        assert_eq!(Difficulty::Hard, hard.difficulty());
+        assert_eq!(0, hard.guesses);
+    }
+
+    // Generated puzzles that need singletons and naked pairs respectively
+    const MEDIUM: &str = r#"
+..4..2.6.
+.....9327
+37.6.....
+..9....72
+..5......
+..6.41..8
+.5...3.19
+...9...3.
+.....6...
+"#;
+
+    const HARD: &str = r#"
+2.....51.
+4.5.1...6
+1......23
+.........
+.6...9.3.
+5...681.9
+...97.8..
+..9354...
+..2......
+"#;
+
+    #[test]
+    fn test_rating_leaves_the_board_alone() {
+        let board = Board::new("easy", easy_sudoku());
+        rate(&board);
+        assert_eq!(easy_sudoku(), board.to_string());
+        assert_eq!(Difficulty::Easy, rate(&Board::new("solved", easy_sudoku_solution())).difficulty());
+    }
+
+    #[test]
+    fn test_target_allows_easier_and_accepts_matching_ratings() {
+        let medium = Rating {
+            hardest: Some(Technique::Singletons),
+            score: 120,
+            guesses: 0,
+        };
+        assert!(Target::Tier(Difficulty::Hard).allows(&medium));
+        assert!(!Target::Tier(Difficulty::Hard).accepts(&medium));
+        assert!(Target::Tier(Difficulty::Medium).accepts(&medium));
+        assert!(!Target::Tier(Difficulty::Easy).allows(&medium));
+        assert!(Target::Score(100, 150).accepts(&medium));
+        assert!(!Target::Score(50, 100).allows(&medium));
+        assert!(Target::Score(130, 200).allows(&medium));
+        assert!(!Target::Score(130, 200).accepts(&medium));
+    }
+}
@@ -289,3 +289,859 @@ pub fn jovial_negative_solution() -> &'static str {
 315842769
 "#
 }
+
+// Other grid sizes, use with Layout::new(box_rows, box_cols)
+// 4x4 with 2x2 subgrids
+
+pub fn kids_4x4() -> &'static str {
+    r#"
+.24.
+....
+....
+..13
+"#
+}
+
+pub fn kids_4x4_solution() -> &'static str {
+    r#"
+3241
+4132
+1324
+2413
+"#
+}
+
+// 6x6 with subgrids of 2 rows and 3 columns
+
+pub fn kids_6x6() -> &'static str {
+    r#"
+5.....
+.24...
+...6..
+.31..5
+......
+1...56
+"#
+}
+
+pub fn kids_6x6_solution() -> &'static str {
+    r#"
+516342
+324561
+452613
+631425
+265134
+143256
+"#
+}
+
+// 12x12 with subgrids of 3 rows and 4 columns, written with numbers instead of letters
+
+pub fn numbers_12x12() -> &'static str {
+    r#"
+ .  .  . 10  . 11  .  .  9  .  .  3
+ .  .  .  5  . 10  .  .  .  1 11  7
+ 1 12  7 11  .  .  .  6  .  4 10  .
+ .  1 11  2  .  .  . 12  4  .  . 10
+ .  6  .  .  .  . 10  .  1  .  .  .
+ 9  .  .  .  1  .  .  .  .  .  7  .
+ .  .  4  .  .  8  .  .  3  . 12  .
+11  7  1  .  .  .  .  .  2 10  .  .
+ .  .  .  .  2  9  4 10  .  .  8  .
+ 3 10  .  . 11  4  .  2  .  .  1  .
+ 2 11  8  .  .  . 12  7  .  .  .  .
+ .  . 12  .  .  .  9  .  .  .  4  8
+"#
+}
+
+pub fn numbers_12x12_solution() -> &'static str {
+    r#"
+482ACB719653
+69358A24C1B7
+1C7B953684A2
+81B2675C493A
+C65743A9182B
+94A312B86C75
+A249781B35C6
+B7183C652A94
+536C294A7B81
+3A96B482571C
+2B8451C7A369
+75C1A693B248
+"#
+}
+
+// 16x16 with 4x4 subgrids, values 10..16 written as A..G
+
+pub fn hardcore_16x16() -> &'static str {
+    r#"
+E93G6D.B...4.2C.
+C.2.......9.D...
+FA.1.5..D.8..3E9
+..6.3G.E....1..A
+.E.7...6..F.852C
+6...G.E...C591.F
+4F19...2A.BD..3.
+.C.8...47..G....
+D.AF...G..28E...
+..7.A.6.E.4...5.
+5.....4..G...A..
+1.9E..2.F..AC7.3
+...6E.19.7GC4..D
+....F..A...E6.85
+.....2G7.8..3...
+91.3..58.ADF.C7.
+"#
+}
+
+pub fn hardcore_16x16_solution() -> &'static str {
+    r#"
+E93G6D8B1FA452C7
+C72541AFGE93D6B8
+FA41257CDB86G3E9
+B86D3G9E5C7214FA
+3EG7DAB694F1852C
+6BDAG7E382C5914F
+4F1958C2A6BD7G3E
+2C5819F473EGAD6B
+D6AF7C3GB528E914
+G37CAF6DE149B852
+528B9E41CG37FAD6
+149E8B25FD6AC7G3
+85B6E31927GC4FAD
+7GC2F4DA391E6B85
+ADF4C2G7685B3E91
+91E3B6584ADF2C7G
+"#
+}
+
+// 25x25 with 5x5 subgrids, values 10..25 written as A..P
+
+pub fn hardcore_25x25() -> &'static str {
+    r#"
+C.HL1.EA..O.7..2MF865.DB.
+.D..KI7O..M.6F2..9C..EJ.A
+.F.2.5.KB.....P4..I7C.9L1
+.JEPA8.M2F..H9.BK.53I7.4O
+.......1.9...DBP..NE..F2M
+.LF.8.....N7J.OMI.6G.9.KC
+3.....J.....G2.18.....PA.
+EP..5....28HFL1K.B3.7J4O.
+74JONH..1.....K.5P..6G...
+6.GM.39....ED.AON47JH...8
+GM.I79LH.K3.B...EOJP.21.6
+.KLCHJ.E..7G4M..6.F..B.5.
+.A......IM6F218CHK.LJPONE
+.OP..F2..1H9L.C53AD..4.I7
+..2..D..5A....NI.MG49..C.
+.E5....4G..1.HF..3K.ON.JP
+K3C.L.NP.7..I.G.2H18A..DB
+...G..C.93.A.E.JP7..1..F.
+.7...182F...C3...E..M.6..
+....2A5B.EP...J...MIKC39L
+4.O...1F..9BK.3EDN..2M8..
+..1HFP..ENJ.O..6.8.M..5..
+.8M....93.D..NE7J..OL1C..
+B5K...OJ.IG.M.....L..ANE.
+P.A.D2MG68.L.CH.95B.4..7J
+"#
+}
+
+pub fn hardcore_25x25_solution() -> &'static str {
+    r#"
+C9HL1NEAPJOI7G42MF8653DBK
+5D3BKI7O4GM86F2L19CHNEJPA
+8F62M53KBDANEJP4OGI7CH9L1
+NJEPA86M2F1CH9LBKD53I7G4O
+IG74OCH1L9K53DBPAJNE86F2M
+HLF18ED5APN7J4OMI26G39BKC
+3B9KC7JNO4I6G2M18LHFEDPA5
+EPDA56GIM28HFL1KCB397J4ON
+74JONHF81LC39BKA5PED6G2MI
+62GMI39CKB5EDPAON47JHFL18
+GM4I79LHCK3DBA5NEOJPF2186
+9KLCHJPENO7G4MI861F2DBA53
+DAB53G47IM6F218CHK9LJPONE
+JOPNEF2681H9LKC53ADBG4MI7
+F1286DB35AEJPONI7MG49LKCH
+AE5DBMI4G6218HF9L3KCON7JP
+K3C9LONPJ74MI6GF2H18A5EDB
+M6IG4KCL93BA5EDJP7ON18HF2
+O7NJP182FHLKC39DBEA5MI6G4
+1H8F2A5BDEPON7JG46MIKC39L
+4IO7JL1FHC9BK53EDNPA2M86G
+LC1HFPADENJ4OI76G82MBK539
+28M6GBK935DPANE7JI4OL1CHF
+B5K394OJ7IG2M86HFCL1PANED
+PNAED2MG68FL1CH395BK4OI7J
+"#
+}
+
+// Jigsaw sudoku: use with Layout::jigsaw(jigsaw_regions())
+
+pub fn jigsaw_regions() -> &'static str {
+    r#"
+aaaaabbcc
+aabbbbccc
+aabbbecfc
+dddeeecfc
+ddddeefff
+dgeeeffii
+dgghhhfii
+ggggghfii
+ghhhhhiii
+"#
+}
+
+pub fn jigsaw_sudoku() -> &'static str {
+    r#"
+......1.8
+....3....
+.1....5..
+...85..63
+3...1....
+..72..4..
+.2.6....7
+..9......
+.8.....29
+"#
+}
+
+pub fn jigsaw_sudoku_solution() -> &'static str {
+    r#"
+254367198
+975438216
+816923574
+192854763
+348716952
+637291485
+521649837
+769582341
+483175629
+"#
+}
+
+// X-Sudoku: both main diagonals must also contain 1..9. Use with Layout::add_diagonals
+
+pub fn x_sudoku() -> &'static str {
+    r#"
+.........
+...5.63.2
+.9...761.
+.....8.7.
+...4..9..
+......2..
+.25.7.8..
+........3
+.....5...
+"#
+}
+
+pub fn x_sudoku_solution() -> &'static str {
+    r#"
+561932784
+847516392
+293847615
+932158476
+176423958
+458769231
+625371849
+719284563
+384695127
+"#
+}
+
+// Windoku: the 4 extra 3x3 windows must also contain 1..9. Use with Layout::add_windows
+
+pub fn windoku() -> &'static str {
+    r#"
+.4....8..
+.1....9..
+......2.5
+.62.4....
+...2.7...
+..4.3....
+.......62
+..5.9....
+.7.......
+"#
+}
+
+pub fn windoku_solution() -> &'static str {
+    r#"
+546923817
+213875946
+897416235
+962541783
+351287694
+784639521
+439758162
+625194378
+178362459
+"#
+}
+
+// Killer Sudoku without given values. Every letter is a cage, followed by the sum of each cage
+
+pub fn killer_cages() -> &'static str {
+    r#"
+CysFFuujj
+qyseliidj
+qyselnndv
+qkkerAhhv
+wggErAAtx
+wgIoGAttx
+fffoGGzcc
+bfpaaHzDc
+bbbaaBBmm
+a=25 b=19 c=19 d=11 e=6 f=20 g=13 h=10 i=8 j=14 k=11 l=9
+m=5 n=11 o=11 p=6 q=13 r=9 s=11 t=15 u=9 v=13 w=8 x=6 y=23
+z=16 A=24 B=12 C=3 D=1 E=5 F=17 G=9 H=5 I=8
+"#
+}
+
+pub fn killer_solution() -> &'static str {
+    r#"
+365897241
+784152639
+192346587
+547283196
+639514872
+218769354
+873421965
+426935718
+951678423
+"#
+}
+
+// Thermo Sudoku: the values on every thermometer increase from the bulb (first square)
+
+pub fn thermo_sudoku() -> &'static str {
+    r#"
+.......6.
+.........
+....6..1.
+..2.....4
+........5
+..1......
+...4.....
+.........
+.........
+"#
+}
+
+pub fn thermo_thermometers() -> &'static str {
+    r#"
+r9c6 r9c7 r8c8 r7c9
+r8c6 r8c5 r7c6 r8c7
+r8c2 r8c1 r7c2 r8c3
+r7c1 r6c1 r5c1 r4c1 r5c2
+r1c7 r1c6 r2c7 r2c8 r2c9
+r7c4 r8c4 r9c4 r9c5
+r6c6 r6c5 r5c4 r6c4
+r4c4 r3c3 r2c3 r1c2 r1c3
+"#
+}
+
+pub fn thermo_sudoku_solution() -> &'static str {
+    r#"
+289175463
+516243789
+734869512
+852317694
+497628135
+361954827
+173496258
+648532971
+925781346
+"#
+}
+
+// Arrow Sudoku: the values along every arrow add up to the value in its circle (first square)
+
+pub fn arrow_sudoku() -> &'static str {
+    r#"
+.........
+8..5.....
+19....6..
+......9..
+.........
+.48......
+..9......
+.....3...
+....4....
+"#
+}
+
+pub fn arrow_arrows() -> &'static str {
+    r#"
+r1c5: r1c6 r1c7 r2c8
+r2c1: r3c1 r2c2 r1c3
+r2c3: r2c4 r2c5
+r9c8: r9c7 r8c6 r7c5
+r7c9: r6c8 r6c7
+r9c4: r8c5 r8c4
+r4c3: r5c2 r5c3 r6c2
+r7c6: r8c7 r7c7
+r3c3: r4c4 r4c5
+"#
+}
+
+pub fn arrow_sudoku_solution() -> &'static str {
+    r#"
+274396185
+836514729
+195872643
+567238914
+321459867
+948761532
+419627358
+752183496
+683945271
+"#
+}
+
+// Kropki Sudoku without given values. White dots join consecutive values, black dots join values where one is double
+// the other. Between squares without a dot, neither holds
+
+pub fn kropki_dots() -> &'static str {
+    r#"
+r1c2 r1c3 black
+r1c2 r2c2 white
+r1c3 r2c3 white
+r1c8 r2c8 black
+r2c6 r3c6 white
+r2c7 r3c7 black
+r3c3 r4c3 black
+r3c4 r3c5 black
+r3c4 r4c4 white
+r3c5 r4c5 black
+r3c9 r4c9 white
+r4c2 r4c3 black
+r4c2 r5c2 white
+r4c3 r4c4 white
+r4c6 r5c6 white
+r5c1 r5c2 white
+r5c2 r5c3 black
+r5c3 r5c4 white
+r5c3 r6c3 white
+r5c5 r6c5 black
+r5c7 r6c7 black
+r5c8 r5c9 white
+r6c1 r6c2 white
+r6c2 r7c2 white
+r6c4 r6c5 white
+r6c4 r7c4 black
+r6c6 r6c7 white
+r6c6 r7c6 white
+r7c2 r7c3 white
+r7c2 r8c2 white
+r7c5 r7c6 black
+r7c5 r8c5 white
+r7c7 r8c7 white
+r7c8 r8c8 white
+r7c9 r8c9 white
+r8c1 r9c1 white
+r8c3 r9c3 white
+r8c4 r8c5 white
+r8c4 r9c4 white
+r8c7 r8c8 black
+r8c8 r8c9 black
+r9c1 r9c2 black
+r9c8 r9c9 white
+negative white black
+"#
+}
+
+pub fn kropki_solution() -> &'static str {
+    r#"
+684952731
+295317468
+713846295
+936728514
+542169387
+871435629
+167284953
+359671842
+428593176
+"#
+}
+
+// XV Sudoku: X joins values that add up to 10, V joins values that add up to 5. Between squares without a mark, neither holds
+
+pub fn xv_sudoku() -> &'static str {
+    r#"
+.........
+.........
+.........
+.........
+...7.....
+.....4...
+.........
+.........
+.........
+"#
+}
+
+pub fn xv_marks() -> &'static str {
+    r#"
+r1c1 r1c2 X
+r1c3 r1c4 X
+r1c6 r1c7 X
+r1c7 r2c7 V
+r1c8 r1c9 X
+r2c2 r2c3 X
+r2c6 r2c7 X
+r2c7 r3c7 X
+r2c8 r3c8 X
+r3c1 r3c2 V
+r3c4 r3c5 X
+r4c3 r4c4 V
+r4c3 r5c3 V
+r4c4 r5c4 X
+r4c6 r4c7 V
+r4c6 r5c6 X
+r4c7 r5c7 V
+r5c3 r5c4 X
+r5c6 r5c7 X
+r5c9 r6c9 X
+r6c6 r6c7 X
+r6c8 r7c8 V
+r7c2 r8c2 X
+r7c3 r8c3 X
+r7c5 r7c6 X
+r7c8 r8c8 X
+r8c3 r8c4 X
+negative X V
+"#
+}
+
+pub fn xv_sudoku_solution() -> &'static str {
+    r#"
+468257391
+719438265
+235916847
+652381479
+843769152
+971524638
+586173924
+124695783
+397842516
+"#
+}
+
+// Anti-knight Sudoku: squares a chess knight's move apart can't contain the same value
+
+pub fn anti_knight_sudoku() -> &'static str {
+    r#"
+2..8.46..
+.........
+973...5..
+..7..8...
+.........
+1...6....
+....9....
+..5.....4
+.........
+"#
+}
+
+pub fn anti_knight_sudoku_solution() -> &'static str {
+    r#"
+251834697
+846975132
+973621548
+537218469
+469753821
+128469375
+384192756
+695387214
+712546983
+"#
+}
+
+// Anti-king Sudoku: squares that touch diagonally can't contain the same value
+
+pub fn anti_king_sudoku() -> &'static str {
+    r#"
+.........
+.13..6...
+....2.7..
+5....1.2.
+.......5.
+.37.9.6.4
+.....7.32
+2..4..9.1
+.........
+"#
+}
+
+pub fn anti_king_sudoku_solution() -> &'static str {
+    r#"
+782539146
+913746285
+465128793
+594861327
+621374859
+837295614
+159687432
+278453961
+346912578
+"#
+}
+
+// Samurai Sudoku: five 9x9 grids, the center grid shares its corner boxes with the other grids
+
+pub fn samurai_sudoku() -> &'static str {
+    r#"
+.....9.6.   .....61..
+...5.....   ..5..29..
+...1267.9   ...8...6.
+..3....7.   ....3....
+96....53.   .4.68..2.
+78..1....   .7...9..4
+....98.............4.
+...4........3.....67.
+.9.7...4..1..6.9.....
+      .....2145
+      8...7....
+      ..6......
+....76..9..72........
+.9..............9.8.3
+..8..3...3......5.1..
+...2...54   ........4
+98.3...2.   ...6...7.
+..2...6.3   9....7.32
+..6......   ..4....1.
+.596.....   .219.8...
+.....45..   8...3....
+"#
+}
+
+pub fn samurai_sudoku_solution() -> &'static str {
+    r#"
+817349265   489376152
+629587413   635142987
+354126789   712895463
+143952876   258431796
+962874531   943687521
+785613924   176259834
+431298657423891763245
+276435198756324518679
+598761342819567924318
+      973682145
+      814975632
+      526134978
+231576489567213846795
+695482731298456791823
+478193265341789352146
+763219854   137529684
+984365127   542683971
+512748693   968417532
+346851972   694275318
+159627348   321968457
+827934516   875134269
+"#
+}
+
+// Sandwich Sudoku: the clues give the sum of the values between the 1 and the 9 in every row and column
+
+pub fn sandwich_sudoku() -> &'static str {
+    r#"
+..9..5...
+.....4...
+.........
+.........
+.........
+3........
+.........
+...5.....
+.........
+"#
+}
+
+pub fn sandwich_clues() -> &'static str {
+    r#"
+rows: 2 20 26 16 13 0 35 20 0
+columns: 8 20 21 33 35 0 8 8 24
+"#
+}
+
+pub fn sandwich_sudoku_solution() -> &'static str {
+    r#"
+739215486
+258964731
+416837295
+947321568
+581679324
+362458917
+123746859
+694583172
+875192643
+"#
+}
+
+// Variant with German whispers, renban lines and even/odd squares, in the format of variant.rs
+
+pub fn lines_sudoku() -> &'static str {
+    r#"
+........3
+3........
+...6.....
+.......3.
+..1......
+5.7..6...
+..6......
+1........
+8....1...
+"#
+}
+
+pub fn lines_constraints() -> &'static str {
+    r#"
+whisper: r7c2 r8c1 r9c2 r8c2 r9c1
+whisper: r4c6 r4c7 r3c8 r2c9
+whisper: r7c4 r8c4 r8c3 r9c4 r8c5
+renban: r5c9 r5c8 r6c9 r7c8 r6c7
+renban: r6c6 r5c7 r5c6 r6c5 r7c5
+renban: r4c8 r3c7 r2c6 r2c5 r1c6
+even: r1c7 r2c4
+odd: r1c4 r1c9 r4c4 r6c3 r6c4 r8c9
+"#
+}
+
+pub fn lines_sudoku_solution() -> &'static str {
+    r#"
+614785293
+359412687
+782639415
+268574931
+941328756
+537196824
+496253178
+123847569
+875961342
+"#
+}
+
+pub fn diagonals_sudoku() -> &'static str {
+    r#"
+...8...6.
+..7.6....
+..5.7.1..
+6........
+..31....6
+.....8...
+..49.....
+.........
+....5....
+"#
+}
+
+pub fn diagonals_constraints() -> &'static str {
+    r#"
+palindrome: r7c2 r6c3 r5c4
+palindrome: r6c2 r6c1 r7c1
+between: r2c6 r1c6 r1c7
+between: r5c8 r6c9 r5c9
+between: r5c6 r5c7 r6c6 r5c5
+little killer: 14 r7c1 down-right
+little killer: 49 r1c9 down-left
+little killer: 11 r1c8 down-right
+little killer: 11 r1c3 down-left
+little killer: 37 r1c7 down-left
+"#
+}
+
+pub fn diagonals_sudoku_solution() -> &'static str {
+    r#"
+391845762
+487261395
+265379148
+628534917
+543197826
+179628534
+714982653
+856413279
+932756481
+"#
+}
+
+pub fn inequality_sudoku() -> &'static str {
+    r#"
+.<.>. .>.>. .<.>.
+v v ^ ^ ^ ^ v v v
+.>.<. .>.<. .<.<.
+v ^ v v ^ v ^ ^ ^
+.<.>. .<.>. .<.<.
+
+4<.<. .<.>. .>.<.
+^ v v ^ v ^ v ^ ^
+.>.<. 7>.<. .>.<.
+v ^ v v ^ v ^ ^ ^
+.<.>. .<.>. .>.<.
+
+.<.<. .>.<. 6>.>.
+^ ^ v v v v ^ ^ ^
+.>.>. .>.<. .<.<.
+^ ^ ^ ^ ^ ^ v v v
+.>.<. .<.<. .<.>.
+"#
+}
+
+pub fn inequality_sudoku_solution() -> &'static str {
+    r#"
+781432596
+539867124
+264591378
+457683912
+816729435
+392154867
+125978643
+643215789
+978346251
+"#
+}
+
+pub fn combined_sudoku() -> &'static str {
+    r#"
+.........
+.........
+.........
+.........
+...3.....
+1........
+.........
+..7......
+.........
+"#
+}
+
+pub fn combined_cages() -> &'static str {
+    r#"
+gdd......
+gfd...c..
+gff...c..
+.........
+..l.e.iin
+hhl.ekknn
+.h..eekaa
+mj.b..ka.
+mjbb.....
+a=10 b=15 c=6 d=21 e=20 f=11 g=13 h=16 i=14 j=6 k=25 l=8 m=7 n=18
+"#
+}
+
+pub fn combined_sudoku_solution() -> &'static str {
+    r#"
+684952731
+579134286
+231876495
+928567143
+745391862
+163248579
+896425317
+457613928
+312789654
+"#
+}
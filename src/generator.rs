@@ -0,0 +1,340 @@
+// Generates new puzzles with exactly one solution
+//
+// 1. Fill a random valid grid: solve an empty board, guessing random values in random order when stuck
+// 2. Remove the givens one by one, in random order. A given is put back if the puzzle has more than one solution
+//    without it
+//
+// The generator is driven by a seedable random number generator: the same seed and layout give the same puzzle
+//
+// To reach a target difficulty, a given is only removed if the puzzle doesn't become harder than the target.
+// If the puzzle that remains is too easy, the generator starts over with a new random grid. It gives up after a
+// number of attempts, as some targets can't be reached at all
+//
+// The givens can follow a symmetry: the squares that the symmetry maps onto each other are removed together
+// A clue mask fixes the squares of the givens instead, e.g. a heart shape. Random grids are tried until the givens
+// in the mask have a single solution (and the target difficulty). The mask takes precedence over the symmetry
+// A mask with fewer than size - 1 givens is rejected: two values that aren't given can always be swapped
+
+use crate::difficulty::{rate, Target};
+use crate::layout::Layout;
+use crate::random::Random;
+use crate::square_value::symbol_of;
+use crate::sudoku::{Board, Game};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+    None,
+    // 180 degrees around the center
+    Rotational,
+    // Mirrored top to bottom
+    Horizontal,
+    // Mirrored left to right
+    Vertical,
+    // Mirrored over the diagonal from top left to bottom right
+    Diagonal,
+    // Mirrored over the diagonal from top right to bottom left
+    AntiDiagonal,
+    // All rotations and mirror images of the square
+    Dihedral,
+}
+
+impl Symmetry {
+    // The squares the symmetry maps a square onto, including the square itself. 0-based positions in a size x size grid
+    pub fn orbit(self, pos: usize, size: usize) -> Vec<usize> {
+        let (row, col) = (pos / size, pos % size);
+        let last = size - 1;
+        let mut places = vec![(row, col)];
+        match self {
+            Symmetry::None => {}
+            Symmetry::Rotational => places.push((last - row, last - col)),
+            Symmetry::Horizontal => places.push((last - row, col)),
+            Symmetry::Vertical => places.push((row, last - col)),
+            Symmetry::Diagonal => places.push((col, row)),
+            Symmetry::AntiDiagonal => places.push((last - col, last - row)),
+            Symmetry::Dihedral => {
+                for (row, col) in [(row, col), (col, row)].iter().copied() {
+                    places.extend([(row, last - col), (last - row, col), (last - row, last - col)].iter());
+                    places.push((row, col));
+                }
+            }
+        }
+        let mut result: Vec<usize> = places.iter().map(|(row, col)| row * size + col).collect();
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+}
+
+pub struct Generator {
+    random: Random,
+    layout: Layout,
+    symmetry: Symmetry,
+    // True for the squares that hold a given
+    mask: Option<Vec<bool>>,
+    // Number of random grids that are tried before giving up
+    attempts: usize,
+}
+
+impl Generator {
+    pub const ATTEMPTS: usize = 100;
+
+    pub fn new(seed: u64) -> Self {
+        Generator::with_layout(seed, Layout::classic())
+    }
+
+    pub fn with_layout(seed: u64, layout: Layout) -> Self {
+        Self {
+            random: Random::new(seed),
+            layout,
+            symmetry: Symmetry::None,
+            mask: None,
+            attempts: Generator::ATTEMPTS,
+        }
+    }
+
+    pub fn symmetry(&mut self, symmetry: Symmetry) {
+        self.symmetry = symmetry;
+    }
+
+    pub fn attempts(&mut self, attempts: usize) {
+        self.attempts = attempts;
+    }
+
+    // Parses the squares of the givens from a grid: '.' is an empty square, any other character a given. E.g.
+    // .xx...xx.
+    // xxxx.xxxx
+    // ...
+    // Empty lines are skipped
+    pub fn clue_mask(&mut self, mask: &str) -> Result<(), String> {
+        let size = self.layout.size();
+        let mut result = Vec::new();
+        for line in mask.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            if result.len() == size * size || line.chars().count() != size {
+                return Err(format!("Unexpected line '{}'", line));
+            }
+            result.extend(line.chars().map(|symbol| symbol != '.'));
+        }
+        if result.len() < size * size {
+            return Err(format!("Found {} lines instead of {}", result.len() / size, size));
+        }
+        let givens = result.iter().filter(|given| **given).count();
+        if givens + 1 < size {
+            return Err(format!("A clue mask needs at least {} givens, found {}", size - 1, givens));
+        }
+        self.mask = Some(result);
+        Ok(())
+    }
+
+    // Returns a puzzle and its solution, or an error if the layout can't be filled or no puzzle fits the clue mask
+    pub fn generate(&mut self, name: &str) -> Result<(Game, Board), String> {
+        self.generate_puzzle(name, None)
+    }
+
+    // Keeps generating until the puzzle has the target difficulty. Returns the puzzle and its solution, or an error
+    // if none of the attempts reached the target
+    pub fn generate_with_difficulty(&mut self, name: &str, target: Target) -> Result<(Game, Board), String> {
+        self.generate_puzzle(name, Some(target))
+    }
+
+    fn generate_puzzle(&mut self, name: &str, target: Option<Target>) -> Result<(Game, Board), String> {
+        for _ in 0..self.attempts {
+            let solution = self.random_solution().ok_or_else(|| "The layout has no solution".to_string())?;
+            let values = Generator::values_of(&solution);
+            let (givens, unique) = match &self.mask {
+                Some(mask) => {
+                    let givens: Vec<usize> = values.iter().zip(mask.iter()).map(|(v, given)| if *given { *v } else { 0 }).collect();
+                    let unique = self.board_of(&givens).count_solutions(2) == 1;
+                    (givens, unique)
+                }
+                None => (self.remove_givens(values, target), true),
+            };
+            if unique && target.map_or(true, |target| target.accepts(&rate(&self.board_of(&givens)))) {
+                return Ok((self.game_of(name, &givens), solution));
+            }
+        }
+        Err(format!("No puzzle found in {} attempts", self.attempts))
+    }
+
+    fn values_of(board: &Board) -> Vec<usize> {
+        (0..board.layout().cells()).map(|pos| board.square_at(pos).value()).collect()
+    }
+
+    fn game_of(&self, name: &str, values: &[usize]) -> Game {
+        Game::with_layout(name, &self.initial_values(values), self.layout.clone())
+    }
+
+    fn board_of(&self, values: &[usize]) -> Board {
+        Board::with_layout("candidate", &self.initial_values(values), self.layout.clone())
+    }
+
+    // A random solved board. None if the layout has no solution at all, e.g. because of too many extra peers
+    pub fn random_solution(&mut self) -> Option<Board> {
+        Board::random_solved_with_layout(self.random.next_u64(), self.layout.clone())
+    }
+
+    // Removes givens in random order, as long as the puzzle keeps a single solution and doesn't get harder than the
+    // target. Squares that the symmetry maps onto each other are removed together. 0 = no given value
+    fn remove_givens(&mut self, mut values: Vec<usize>, target: Option<Target>) -> Vec<usize> {
+        let mut orbits: Vec<Vec<usize>> = Vec::new();
+        for pos in 0..values.len() {
+            if !orbits.iter().any(|orbit| orbit.contains(&pos)) {
+                orbits.push(self.symmetry.orbit(pos, self.layout.size()));
+            }
+        }
+        self.random.shuffle(&mut orbits);
+        for orbit in orbits {
+            let givens: Vec<usize> = orbit.iter().map(|pos| values[*pos]).collect();
+            for pos in orbit.iter() {
+                values[*pos] = 0;
+            }
+            let board = self.board_of(&values);
+            let allowed = board.count_solutions(2) == 1 && target.map_or(true, |target| target.allows(&rate(&board)));
+            if !allowed {
+                for (pos, given) in orbit.iter().zip(givens.iter()) {
+                    values[*pos] = *given;
+                }
+            }
+        }
+        values
+    }
+
+    // The values in the input format of a board: one row per line, '.' for unknown squares
+    fn initial_values(&self, values: &[usize]) -> String {
+        let mut result = String::new();
+        for row in values.chunks(self.layout.size()) {
+            result.extend(row.iter().map(|value| if *value == 0 { '.' } else { symbol_of(*value) }));
+            result.push('\n');
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+
+    #[test]
+    fn test_generated_puzzle_has_a_unique_solution() {
+        let (game, solution) = Generator::new(1).generate("generated").unwrap();
+        assert!(solution.solved());
+        assert_eq!(1, game.count_solutions(2));
+        assert!(game.is_minimal());
+        let mut solved = game;
+        solved.solve();
+        assert_eq!(solution.to_string(), solved.to_string());
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_puzzle() {
+        let (first, _) = Generator::new(5).generate("first").unwrap();
+        let (second, _) = Generator::new(5).generate("second").unwrap();
+        let (other, _) = Generator::new(6).generate("other").unwrap();
+        assert_eq!(first.to_string(), second.to_string());
+        assert_ne!(first.to_string(), other.to_string());
+    }
+
+    #[test]
+    fn test_generate_other_grid_sizes() {
+        let (game, solution) = Generator::with_layout(2, Layout::new(2, 3)).generate("6x6").unwrap();
+        assert_eq!(6, solution.size());
+        assert_eq!(1, game.count_solutions(2));
+    }
+
+    #[test]
+    fn test_generate_to_a_difficulty_tier() {
+        let target = Target::Tier(Difficulty::Medium);
+        let (game, solution) = Generator::new(6).generate_with_difficulty("medium", target).unwrap();
+        let board = Board::new("medium", &game.to_string());
+        assert!(target.accepts(&rate(&board)));
+        assert_eq!(1, board.count_solutions(2));
+        assert!(solution.solved());
+    }
+
+    #[test]
+    fn test_generate_to_a_score_band() {
+        let target = Target::Score(150, 170);
+        let (game, _) = Generator::new(4).generate_with_difficulty("band", target).unwrap();
+        let score = rate(&Board::new("band", &game.to_string())).score;
+        assert!((150..=170).contains(&score));
+    }
+
+    #[test]
+    fn test_layout_without_solution_is_reported() {
+        let mut layout = Layout::new(2, 2);
+        layout.add_anti_king();
+        let mut generator = Generator::with_layout(1, layout);
+        assert!(generator.random_solution().is_none());
+        assert_eq!(Err("The layout has no solution".to_string()), generator.generate("anti-king").map(|_| ()));
+    }
+
+    #[test]
+    fn test_orbits_of_symmetries() {
+        assert_eq!(vec![1], Symmetry::None.orbit(1, 9));
+        assert_eq!(vec![1, 79], Symmetry::Rotational.orbit(1, 9));
+        assert_eq!(vec![40], Symmetry::Rotational.orbit(40, 9));
+        assert_eq!(vec![1, 73], Symmetry::Horizontal.orbit(1, 9));
+        assert_eq!(vec![1, 7], Symmetry::Vertical.orbit(1, 9));
+        assert_eq!(vec![1, 9], Symmetry::Diagonal.orbit(1, 9));
+        assert_eq!(vec![1, 71], Symmetry::AntiDiagonal.orbit(1, 9));
+        assert_eq!(vec![1, 7, 9, 17, 63, 71, 73, 79], Symmetry::Dihedral.orbit(1, 9));
+        assert_eq!(vec![0, 8, 72, 80], Symmetry::Dihedral.orbit(0, 9));
+    }
+
+    #[test]
+    fn test_givens_follow_the_symmetry() {
+        let mut generator = Generator::new(8);
+        generator.symmetry(Symmetry::Rotational);
+        let (game, _) = generator.generate("rotational").unwrap();
+        let board = Board::new("rotational", &game.to_string());
+        for pos in 0..81 {
+            assert_eq!(board.square_at(pos).has_known_value(), board.square_at(80 - pos).has_known_value());
+        }
+        assert_eq!(1, board.count_solutions(2));
+    }
+
+    #[test]
+    fn test_givens_fill_the_clue_mask() {
+        let heart = "\n.xx...xx.\nxxxx.xxxx\nxxxxxxxxx\nxxxxxxxxx\nxxxxxxxxx\n.xxxxxxx.\n..xxxxx..\n...xxx...\n....x....\n";
+        let mut generator = Generator::new(9);
+        generator.clue_mask(heart).unwrap();
+        let (game, _) = generator.generate("heart").unwrap();
+        let puzzle = game.to_string();
+        let shape: String = puzzle.chars().map(|symbol| if symbol == '.' || symbol == '\n' { symbol } else { 'x' }).collect();
+        assert_eq!(heart, shape);
+        assert_eq!(1, game.count_solutions(2));
+    }
+
+    #[test]
+    fn test_invalid_clue_mask_is_rejected() {
+        let mut generator = Generator::with_layout(1, Layout::new(2, 2));
+        assert_eq!(Err("Unexpected line 'x..'".to_string()), generator.clue_mask("x..\n"));
+        assert_eq!(Err("Found 2 lines instead of 4".to_string()), generator.clue_mask("x...\n...x\n"));
+        assert_eq!(Err("Unexpected line '....'".to_string()), generator.clue_mask("....\n....\n....\n....\n....\n"));
+        assert_eq!(
+            Err("A clue mask needs at least 3 givens, found 1".to_string()),
+            generator.clue_mask("x...\n....\n....\n....\n")
+        );
+        let one_clue = format!("x........\n{}", ".........\n".repeat(8));
+        assert_eq!(
+            Err("A clue mask needs at least 8 givens, found 1".to_string()),
+            Generator::new(1).clue_mask(&one_clue)
+        );
+    }
+
+    #[test]
+    fn test_generator_gives_up_on_unreachable_puzzles() {
+        // A 4x4 puzzle needs at least 4 givens to have a single solution
+        let mut generator = Generator::with_layout(1, Layout::new(2, 2));
+        generator.clue_mask("x...\n.x..\n..x.\n....\n").unwrap();
+        assert_eq!(Err("No puzzle found in 100 attempts".to_string()), generator.generate("three").map(|_| ()));
+
+        let mut generator = Generator::with_layout(1, Layout::new(2, 2));
+        generator.attempts(5);
+        let target = Target::Score(0, 0);
+        assert_eq!(
+            Err("No puzzle found in 5 attempts".to_string()),
+            generator.generate_with_difficulty("zero", target).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,162 @@
+// Inequality Sudoku (also Greater Than Sudoku): markers between neighbouring squares tell which of the two values is
+// the higher one. Often all squares next to each other in a box have a marker, and there are few or no given values
+//
+// The possible values of both squares are limited to a range:
+// - The smaller square must be lower than the highest possible value of the larger square
+// - The larger square must be higher than the lowest possible value of the smaller square
+// Chains of markers are followed because the board keeps pruning until no more progress is made
+
+use crate::constraint::Constraint;
+use crate::layout::Layout;
+use crate::sudoku::Board;
+
+pub struct Inequality {
+    // (smaller, larger) positions of each marker
+    pairs: Vec<(usize, usize)>,
+}
+
+impl Inequality {
+    pub fn new(pairs: Vec<(usize, usize)>) -> Self {
+        Self { pairs }
+    }
+
+    // Parses a puzzle where the markers are placed between the squares. Squares are at every other character of a
+    // row, using the same symbols as the initial values of a board. Between two squares of a row, '<' or '>' compare
+    // them, a space means there is no marker. A line with '^' or 'v' under a square compares it with the square below:
+    // '^' means the upper value is the smaller one, 'v' means it is the larger one. E.g.
+    // 1<. . .
+    // ^     v
+    // . .>. .
+    // Returns the initial values, one row per line, and the constraint. Empty lines are skipped
+    pub fn parse(puzzle: &str, layout: &Layout) -> Result<(String, Self), String> {
+        let size = layout.size();
+        let mut initial = String::new();
+        let mut pairs = Vec::new();
+        let mut row = 0;
+        let mut vertical: Option<&str> = None;
+        for line in puzzle.lines().map(|line| line.trim_end()).filter(|line| !line.is_empty()) {
+            let symbols: Vec<char> = line.chars().collect();
+            if symbols.iter().all(|symbol| matches!(symbol, ' ' | '^' | 'v')) {
+                if row == 0 || row == size || vertical.is_some() {
+                    return Err(format!("Unexpected line '{}'", line));
+                }
+                vertical = Some(line);
+                continue;
+            }
+            if row == size {
+                return Err(format!("Unexpected line '{}'", line));
+            }
+            if symbols.len() < 2 * size - 1 {
+                return Err(format!("Line '{}' is too short", line));
+            }
+            for col in 0..size {
+                initial.push(symbols[2 * col]);
+                if col + 1 < size {
+                    let (left, right) = (row * size + col, row * size + col + 1);
+                    match symbols[2 * col + 1] {
+                        '<' => pairs.push((left, right)),
+                        '>' => pairs.push((right, left)),
+                        ' ' => {}
+                        marker => return Err(Inequality::unexpected(marker, layout, left, right)),
+                    }
+                }
+            }
+            initial.push('\n');
+            if let Some(markers) = vertical.take() {
+                for (index, marker) in markers.chars().enumerate().filter(|(_, marker)| *marker != ' ') {
+                    if index % 2 == 1 || index / 2 >= size {
+                        return Err(format!("Marker '{}' in line '{}' is not below a square", marker, markers));
+                    }
+                    let (above, below) = ((row - 1) * size + index / 2, row * size + index / 2);
+                    pairs.push(if marker == '^' { (above, below) } else { (below, above) });
+                }
+            }
+            row += 1;
+        }
+        if row < size {
+            return Err(format!("Found {} rows instead of {}", row, size));
+        }
+        Ok((initial, Inequality::new(pairs)))
+    }
+
+    fn unexpected(marker: char, layout: &Layout, pos: usize, other: usize) -> String {
+        format!(
+            "Unexpected marker '{}' between {} and {}",
+            marker,
+            layout.name_of(pos),
+            layout.name_of(other)
+        )
+    }
+
+    pub fn pairs(&self) -> &[(usize, usize)] {
+        &self.pairs
+    }
+}
+
+impl Constraint for Inequality {
+    fn prune(&self, board: &mut Board) -> bool {
+        let size = board.size();
+        let mut pruned = false;
+        for (smaller, larger) in self.pairs.iter() {
+            let highest = board.square_at(*larger).highest_value();
+            pruned |= board.eliminate_outside(*smaller, 1, highest.saturating_sub(1));
+            let lowest = board.square_at(*smaller).value();
+            pruned |= board.eliminate_outside(*larger, lowest + 1, size);
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.pairs
+            .iter()
+            .all(|(smaller, larger)| board.square_at(*smaller).value() < board.square_at(*larger).value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_markers_between_squares() {
+        let layout = Layout::new(2, 2);
+        let (initial, inequality) = Inequality::parse("\n1<. . .\n^     v\n. .>. .\n\n. . . .\n. . .<4\n", &layout).unwrap();
+        assert_eq!("1...\n....\n....\n...4\n", initial);
+        assert_eq!(&[(0, 1), (6, 5), (0, 4), (7, 3), (14, 15)], inequality.pairs());
+    }
+
+    #[test]
+    fn test_invalid_markers_are_rejected() {
+        let layout = Layout::new(2, 2);
+        let error = |puzzle| Inequality::parse(puzzle, &layout).map(|_| ()).unwrap_err();
+        assert_eq!("Unexpected marker '=' between r1c1 and r1c2", error("1=. . .\n. . . .\n. . . .\n. . . ."));
+        assert_eq!("Line '1 2' is too short", error("1 2\n. . . .\n. . . .\n. . . ."));
+        assert_eq!("Unexpected line '^'", error("^\n. . . .\n. . . .\n. . . .\n. . . ."));
+        assert_eq!("Marker 'v' in line ' v' is not below a square", error(". . . .\n v\n. . . .\n. . . .\n. . . ."));
+        assert_eq!("Found 3 rows instead of 4", error(". . . .\n. . . .\n. . . ."));
+        assert_eq!("Unexpected line '. . . .'", error(". . . .\n. . . .\n. . . .\n. . . .\n. . . ."));
+    }
+
+    #[test]
+    fn test_markers_limit_ranges_of_both_squares() {
+        let inequality = Inequality::new(vec![(0, 1), (1, 2)]);
+        let mut board = Board::new("inequality", "");
+        assert!(inequality.prune(&mut board));
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], board.square_at(0).candidates());
+        assert_eq!(vec![2, 3, 4, 5, 6, 7, 8], board.square_at(1).candidates());
+        assert_eq!(vec![3, 4, 5, 6, 7, 8, 9], board.square_at(2).candidates());
+
+        // A known value is followed along the chain
+        let mut board = Board::new("inequality", "..3......");
+        while inequality.prune(&mut board) {}
+        assert_eq!(vec![1], board.square_at(0).candidates());
+        assert_eq!(vec![2], board.square_at(1).candidates());
+    }
+
+    #[test]
+    fn test_is_satisfied_compares_values() {
+        let inequality = Inequality::new(vec![(0, 1), (2, 1)]);
+        assert!(inequality.is_satisfied(&Board::new("ok", "192......")));
+        assert!(!inequality.is_satisfied(&Board::new("wrong", "912......")));
+    }
+}
@@ -0,0 +1,356 @@
+// Killer Sudoku: the grid is divided in cages. The values in a cage add up to the cage's sum and can't repeat
+// A Killer Sudoku often has no given values at all
+//
+// Two techniques prune the possible values:
+// - Cage combinations: only values that are part of a combination of distinct values with the right sum are kept
+//   Example: a cage of 2 squares with sum 3 can only contain 1 and 2
+// - The "45 rule": every row, column and box contains each value once, so its values add up to 45 (in a 9x9 grid)
+//   Subtracting the sums of the cages that lie completely inside the unit gives the sum of the remaining squares.
+//   Those squares are in the same unit, so they are distinct too: they are treated as an extra cage
+
+use crate::constraint::Constraint;
+use crate::layout::Layout;
+use crate::sudoku::Board;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cage {
+    pub sum: usize,
+    pub squares: Vec<usize>,
+}
+
+pub struct Killer {
+    cages: Vec<Cage>,
+    innies: Vec<Cage>,
+}
+
+impl Killer {
+    // Squares left over by the 45 rule are only used if there are at most this many.
+    // Bigger groups rarely lead to pruning, but are expensive to check
+    const MAX_INNIES: usize = 4;
+
+    pub fn new(cages: Vec<Cage>, layout: &Layout) -> Self {
+        let mut innies = Vec::new();
+        let total: usize = (1..=layout.size()).sum();
+        for unit in layout.units().iter() {
+            let inside: Vec<&Cage> = cages
+                .iter()
+                .filter(|cage| cage.squares.iter().all(|pos| unit.contains(pos)))
+                .collect();
+            let remaining: Vec<usize> = unit
+                .iter()
+                .filter(|pos| !inside.iter().any(|cage| cage.squares.contains(pos)))
+                .cloned()
+                .collect();
+            let covered: usize = inside.iter().map(|cage| cage.sum).sum();
+            if !remaining.is_empty() && remaining.len() <= Killer::MAX_INNIES && covered < total {
+                innies.push(Cage {
+                    sum: total - covered,
+                    squares: remaining,
+                });
+            }
+        }
+        Self { cages, innies }
+    }
+
+    // Parses cages from a grid with a cage name per square, followed by the sum of every cage
+    // Any character except '.' can be used as a cage name. A '.' is a square without a cage. E.g.
+    // aabc
+    // dbbc
+    // ...
+    // a=3 b=10 c=7 d=4
+    pub fn parse(cages: &str, layout: &Layout) -> Result<Self, String> {
+        let size = layout.size();
+        let mut squares: HashMap<char, Vec<usize>> = HashMap::new();
+        let mut names: Vec<char> = Vec::new();
+        let mut sums: HashMap<char, usize> = HashMap::new();
+        let mut row = 0;
+        for line in cages.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            if line.contains('=') {
+                for declaration in line.split_whitespace() {
+                    let (name, sum) = parse_sum(declaration)?;
+                    sums.insert(name, sum);
+                }
+            } else {
+                if row == size || line.chars().count() != size {
+                    return Err(format!("Unexpected line '{}'", line));
+                }
+                for (col, name) in line.chars().enumerate() {
+                    if name != '.' {
+                        if !squares.contains_key(&name) {
+                            names.push(name);
+                        }
+                        squares.entry(name).or_default().push(row * size + col);
+                    }
+                }
+                row += 1;
+            }
+        }
+        let mut result = Vec::new();
+        for name in names.iter() {
+            let sum = match sums.get(name) {
+                Some(sum) => *sum,
+                None => return Err(format!("Cage '{}' has no sum", name)),
+            };
+            let squares = squares[name].clone();
+            if squares.len() > size {
+                return Err(format!("Cage '{}' has more than {} squares", name, size));
+            }
+            result.push(Cage { sum, squares });
+        }
+        if let Some(name) = sums.keys().find(|name| !squares.contains_key(name)) {
+            return Err(format!("Cage '{}' has no squares", name));
+        }
+        Ok(Killer::new(result, layout))
+    }
+
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    // Extra cages derived from the 45 rule
+    pub fn innies(&self) -> &[Cage] {
+        &self.innies
+    }
+}
+
+fn parse_sum(declaration: &str) -> Result<(char, usize), String> {
+    let mut parts = declaration.splitn(2, '=');
+    let name = parts.next().unwrap_or("");
+    let sum = parts.next().unwrap_or("");
+    let mut name_chars = name.chars();
+    match (name_chars.next(), name_chars.next(), sum.parse::<usize>()) {
+        (Some(name), None, Ok(sum)) => Ok((name, sum)),
+        _ => Err(format!("Invalid cage sum '{}'", declaration)),
+    }
+}
+
+impl Constraint for Killer {
+    fn prune(&self, board: &mut Board) -> bool {
+        let mut pruned = false;
+        for cage in self.cages.iter().chain(self.innies.iter()) {
+            pruned |= prune_cage(board, cage);
+        }
+        pruned
+    }
+
+    // The squares left over by the 45 rule are checked too: every solution satisfies them
+    fn is_consistent(&self, board: &Board) -> bool {
+        self.cages.iter().chain(self.innies.iter()).all(|cage| cage_is_consistent(board, cage))
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.cages.iter().all(|cage| {
+            let mut values: Vec<usize> = cage.squares.iter().map(|pos| board.square_at(*pos).value()).collect();
+            let sum: usize = values.iter().sum();
+            values.sort_unstable();
+            values.dedup();
+            sum == cage.sum && values.len() == cage.squares.len()
+        })
+    }
+}
+
+// The known values of a cage don't repeat and don't exceed its sum, and the unknown squares can still make up the rest
+// with distinct values that aren't used yet
+fn cage_is_consistent(board: &Board, cage: &Cage) -> bool {
+    // One bit per value, at most 25 values
+    let mut used = 0u32;
+    let mut known = 0;
+    let mut unknown = 0;
+    for square in cage.squares.iter().map(|pos| board.square_at(*pos)) {
+        if square.has_known_value() {
+            let bit = 1 << square.value();
+            if used & bit != 0 {
+                return false;
+            }
+            used |= bit;
+            known += square.value();
+        } else {
+            unknown += 1;
+        }
+    }
+    if known > cage.sum {
+        return false;
+    }
+    let unused = (1..=board.size()).filter(|value| used & (1 << value) == 0);
+    let lowest: usize = unused.clone().take(unknown).sum();
+    let highest: usize = unused.rev().take(unknown).sum();
+    lowest <= cage.sum - known && cage.sum - known <= highest
+}
+
+// Removes all values that are not part of any combination of distinct values with the cage's sum
+fn prune_cage(board: &mut Board, cage: &Cage) -> bool {
+    let candidates: Vec<Vec<usize>> = cage
+        .squares
+        .iter()
+        .map(|pos| board.square_at(*pos).candidates())
+        .collect();
+    let mut supported = vec![vec![false; board.size() + 1]; cage.squares.len()];
+    let mut chosen = Vec::new();
+    find_combinations(&candidates, cage.sum, &mut chosen, &mut supported);
+
+    let mut pruned = false;
+    for (index, pos) in cage.squares.iter().enumerate() {
+        for value in candidates[index].iter() {
+            if !supported[index][*value] {
+                pruned |= board.eliminate(*pos, *value);
+            }
+        }
+    }
+    pruned
+}
+
+// Tries all combinations of distinct values, one per square, square by square
+// Every value in a combination that adds up to the sum is marked as supported
+pub(crate) fn find_combinations(candidates: &[Vec<usize>], remaining: usize, chosen: &mut Vec<usize>, supported: &mut [Vec<bool>]) {
+    let index = chosen.len();
+    if index == candidates.len() {
+        if remaining == 0 {
+            for (square, value) in chosen.iter().enumerate() {
+                supported[square][*value] = true;
+            }
+        }
+        return;
+    }
+    // The remaining squares need at least their smallest and at most their largest candidate
+    let rest = &candidates[index + 1..];
+    let minimum: usize = rest.iter().map(|c| c.first().copied().unwrap_or(0)).sum();
+    let maximum: usize = rest.iter().map(|c| c.last().copied().unwrap_or(0)).sum();
+    for value in candidates[index].iter() {
+        if *value + minimum <= remaining && *value + maximum >= remaining && !chosen.contains(value) {
+            chosen.push(*value);
+            find_combinations(candidates, remaining - value, chosen, supported);
+            chosen.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::*;
+    use crate::sudoku::Game;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_parse_cages() {
+        let layout = Layout::new(2, 2);
+        let killer = Killer::parse("\naabc\ndbbc\ndeef\nggff\na=3 b=10 c=7\nd=4 e=7 f=6 g=3\n", &layout).unwrap();
+        assert_eq!(7, killer.cages().len());
+        assert_eq!(Cage { sum: 3, squares: vec![0, 1] }, killer.cages()[0]);
+        assert_eq!(Cage { sum: 10, squares: vec![2, 5, 6] }, killer.cages()[1]);
+        assert_eq!(Cage { sum: 6, squares: vec![11, 14, 15] }, killer.cages()[5]);
+    }
+
+    #[test]
+    fn test_invalid_cages_are_rejected() {
+        let layout = Layout::new(2, 2);
+        assert_eq!(
+            Err("Cage 'b' has no sum".to_string()),
+            Killer::parse("aabb\naabb\ncccc\ndddd\na=10 c=10 d=10", &layout).map(|_| ())
+        );
+        assert_eq!(
+            Err("Cage 'x' has no squares".to_string()),
+            Killer::parse("aabb\naabb\ncccc\ndddd\na=10 b=10 c=10 d=10 x=3", &layout).map(|_| ())
+        );
+        assert_eq!(
+            Err("Invalid cage sum 'a=ten'".to_string()),
+            Killer::parse("aabb\na=ten", &layout).map(|_| ())
+        );
+        assert_eq!(
+            Err("Cage 'a' has more than 4 squares".to_string()),
+            Killer::parse("aaaa\naabb\nbbbb\nbbbb\na=10 b=20", &layout).map(|_| ())
+        );
+        assert_eq!(
+            Err("Unexpected line 'aab'".to_string()),
+            Killer::parse("aab\na=10", &layout).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_cage_combinations_prune_values() {
+        let layout = Layout::classic();
+        let cages = vec![
+            Cage { sum: 3, squares: vec![0, 1] },
+            Cage { sum: 24, squares: vec![9, 10, 11] },
+        ];
+        let killer = Killer::new(cages, &layout);
+        let mut board = Board::new("cages", "");
+        assert!(killer.prune(&mut board));
+
+        assert_eq!(vec![1, 2], board.square_at(0).candidates());
+        assert_eq!(vec![1, 2], board.square_at(1).candidates());
+        assert_eq!(vec![7, 8, 9], board.square_at(9).candidates());
+        assert!(!killer.prune(&mut board));
+    }
+
+    #[test]
+    fn test_45_rule_finds_remaining_squares() {
+        let layout = Layout::new(2, 2);
+        // First row: cages of 1 and 2 squares inside the row, the last square sticks out into a cage below it
+        let cages = vec![
+            Cage { sum: 4, squares: vec![0] },
+            Cage { sum: 3, squares: vec![1, 2] },
+            Cage { sum: 4, squares: vec![3, 7] },
+        ];
+        let killer = Killer::new(cages, &layout);
+        assert!(killer.innies().contains(&Cage { sum: 3, squares: vec![3] }));
+
+        let mut board = Board::with_layout("innies", "", layout);
+        while killer.prune(&mut board) {}
+        assert_eq!(vec![3], board.square_at(3).candidates());
+        assert_eq!(vec![1], board.square_at(7).candidates());
+    }
+
+    #[test]
+    fn test_is_satisfied_checks_sums_and_repeats() {
+        let layout = Layout::new(2, 2);
+        let killer = Killer::new(vec![Cage { sum: 4, squares: vec![0, 4] }], &layout);
+        assert!(killer.is_satisfied(&Board::with_layout("ok", "1...\n3...", Layout::new(2, 2))));
+        assert!(!killer.is_satisfied(&Board::with_layout("sum", "1...\n2...", Layout::new(2, 2))));
+        let killer = Killer::new(vec![Cage { sum: 4, squares: vec![0, 5] }], &layout);
+        assert!(!killer.is_satisfied(&Board::with_layout("repeat", "2...\n.2..", Layout::new(2, 2))));
+    }
+
+    #[test]
+    fn test_is_consistent_checks_known_values_and_what_remains() {
+        let layout = Layout::new(2, 2);
+        let killer = Killer::new(vec![Cage { sum: 6, squares: vec![0, 4, 8] }], &layout);
+        let board = |values| Board::with_layout("cage", values, Layout::new(2, 2));
+        assert!(killer.is_consistent(&board("1...\n2...")));
+        // The third square would have to be 3
+        assert!(killer.is_consistent(&board("1...\n2...\n3...")));
+        assert!(!killer.is_consistent(&board("1...\n1...")));
+        assert!(!killer.is_consistent(&board("4...\n3...")));
+        // The third square would have to be 0
+        assert!(!killer.is_consistent(&board("4...\n2...")));
+        // Two distinct values other than 4 add up to at least 1 + 2
+        assert!(!killer.is_consistent(&board("4...")));
+        let killer = Killer::new(vec![Cage { sum: 9, squares: vec![0, 4, 8] }], &layout);
+        // Two distinct values other than 1 add up to at most 4 + 3
+        assert!(!killer.is_consistent(&board("1...")));
+        assert!(killer.is_consistent(&board("4...")));
+    }
+
+    #[test]
+    fn test_solve_killer_without_givens() {
+        let layout = Layout::classic();
+        let killer = Rc::new(Killer::parse(killer_cages(), &layout).unwrap());
+        let mut board = Board::new("killer", "");
+        board.add_constraint(killer.clone());
+        board.solve();
+        assert!(!board.contains_contradiction());
+        // Single square cage is known immediately
+        assert_eq!(1, board.square(8, 8).value());
+
+        let mut game = Game::from_board(board);
+        assert!(game.solve());
+        assert_eq!(killer_solution(), game.to_string());
+        let solution = Board::new("solution", killer_solution());
+        assert!(killer.is_satisfied(&solution));
+        for cage in killer.cages().iter() {
+            let sum: usize = cage.squares.iter().map(|pos| solution.square_at(*pos).value()).sum();
+            assert_eq!(cage.sum, sum);
+        }
+    }
+}
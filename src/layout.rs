@@ -0,0 +1,550 @@
+// The layout describes the shape of a sudoku grid
+// A grid of size N has N rows and N columns and contains values 1..=N
+// The grid is divided in boxes (subgrids) of box_rows x box_cols squares, with box_rows * box_cols = N
+// E.g. the classic 9x9 sudoku has 3x3 boxes, a 6x6 sudoku has boxes of 2 rows and 3 columns
+//
+// Rows, columns and boxes are "units": groups of N squares that must contain each value exactly once
+// Squares that share a unit are "peers": they must have distinct values
+// Squares are identified by their position 0..N*N, row by row. Rows and columns are 1-based
+//
+// In a jigsaw sudoku, the boxes are replaced by N irregular regions of N connected squares
+// Variants can add extra units, e.g. the two main diagonals in an X-Sudoku or the windows in a Windoku
+// Variants can also add extra peers that don't form a unit, e.g. squares a chess knight's move apart
+
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub struct Layout {
+    size: usize,
+    box_rows: usize,
+    box_cols: usize,
+    regions: Vec<usize>,
+    jigsaw: bool,
+    windows: Vec<bool>,
+    units: Vec<Vec<usize>>,
+    extra_peers: Vec<(usize, usize)>,
+    units_of: Vec<Vec<usize>>,
+    peers: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    // Largest supported grid, limited by the number of possible values a SquareValue can hold
+    pub const MAX_SIZE: usize = 25;
+
+    pub fn new(box_rows: usize, box_cols: usize) -> Self {
+        let size = box_rows * box_cols;
+        assert!(
+            (1..=Layout::MAX_SIZE).contains(&size),
+            "Grid size {} is not supported",
+            size
+        );
+        // Boxes are numbered row by row, just like squares
+        let regions = (0..size * size)
+            .map(|pos| (pos / size) / box_rows * box_rows + (pos % size) / box_cols)
+            .collect();
+        Layout::with_regions(size, box_rows, box_cols, regions, false)
+    }
+
+    // A jigsaw layout, parsed from a grid of region names: one line per row, one character per square
+    // Squares with the same character belong to the same region. E.g. for a 4x4 grid:
+    // aabb
+    // abbb
+    // accd
+    // ccdd
+    // Empty lines are skipped. Every region must contain N connected squares
+    pub fn jigsaw(regions: &str) -> Result<Self, String> {
+        let rows: Vec<Vec<char>> = regions
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| line.chars().collect())
+            .collect();
+        let size = rows.len();
+        if !(1..=Layout::MAX_SIZE).contains(&size) {
+            return Err(format!("Grid size {} is not supported", size));
+        }
+        let mut names: HashMap<char, usize> = HashMap::new();
+        let mut region_of = Vec::new();
+        for (row, line) in rows.iter().enumerate() {
+            if line.len() != size {
+                return Err(format!(
+                    "Row {} has {} squares instead of {}",
+                    row + 1,
+                    line.len(),
+                    size
+                ));
+            }
+            for name in line.iter() {
+                let next = names.len();
+                region_of.push(*names.entry(*name).or_insert(next));
+            }
+        }
+        if names.len() != size {
+            return Err(format!("Found {} regions instead of {}", names.len(), size));
+        }
+        let layout = Layout::with_regions(size, size, 1, region_of, true);
+        let mut names_in_order: Vec<(usize, char)> = names.iter().map(|(n, r)| (*r, *n)).collect();
+        names_in_order.sort_unstable();
+        for (region, name) in names_in_order.iter() {
+            let squares = &layout.units[2 * size + region];
+            if squares.len() != size {
+                return Err(format!(
+                    "Region '{}' has {} squares instead of {}",
+                    name,
+                    squares.len(),
+                    size
+                ));
+            }
+            if !layout.connected(squares) {
+                return Err(format!("Region '{}' is not connected", name));
+            }
+        }
+        Ok(layout)
+    }
+
+    fn with_regions(
+        size: usize,
+        box_rows: usize,
+        box_cols: usize,
+        regions: Vec<usize>,
+        jigsaw: bool,
+    ) -> Self {
+        let mut result = Self {
+            size,
+            box_rows,
+            box_cols,
+            windows: vec![false; size * size],
+            regions,
+            jigsaw,
+            units: Vec::new(),
+            extra_peers: Vec::new(),
+            units_of: Vec::new(),
+            peers: Vec::new(),
+        };
+        for row in 1..=size {
+            let unit = (1..=size).map(|col| result.position_of(row, col)).collect();
+            result.units.push(unit);
+        }
+        for col in 1..=size {
+            let unit = (1..=size).map(|row| result.position_of(row, col)).collect();
+            result.units.push(unit);
+        }
+        for region in 0..size {
+            let unit = (0..result.cells())
+                .filter(|pos| result.regions[*pos] == region)
+                .collect();
+            result.units.push(unit);
+        }
+        result.connect_peers();
+        result
+    }
+
+    // The classic 9x9 sudoku with 3x3 boxes
+    pub fn classic() -> Self {
+        Layout::new(3, 3)
+    }
+
+    // Number of values, rows and columns
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // True if the boxes are replaced by irregular regions
+    pub fn is_jigsaw(&self) -> bool {
+        self.jigsaw
+    }
+
+    // True if the only units are the rows, columns and regular boxes, without extra peers
+    pub fn is_plain(&self) -> bool {
+        !self.jigsaw && self.units.len() == 3 * self.size && self.extra_peers.is_empty()
+    }
+
+    // Index 0..N of the box or jigsaw region the square belongs to
+    pub fn region_of(&self, pos: usize) -> usize {
+        self.regions[pos]
+    }
+
+    pub fn box_rows(&self) -> usize {
+        self.box_rows
+    }
+
+    pub fn box_cols(&self) -> usize {
+        self.box_cols
+    }
+
+    // Number of squares in the grid
+    pub fn cells(&self) -> usize {
+        self.size * self.size
+    }
+
+    pub fn position_of(&self, row: usize, col: usize) -> usize {
+        (row - 1) * self.size + (col - 1)
+    }
+
+    pub fn row_of(&self, pos: usize) -> usize {
+        pos / self.size + 1
+    }
+
+    pub fn col_of(&self, pos: usize) -> usize {
+        pos % self.size + 1
+    }
+
+    // Parses a square in the usual "r<row>c<col>" notation, e.g. r1c2 is row 1, column 2
+    pub fn parse_square(&self, square: &str) -> Result<usize, String> {
+        let lower = square.to_lowercase();
+        let coordinates = lower.strip_prefix('r').and_then(|rest| rest.split_once('c'));
+        match coordinates.map(|(row, col)| (row.parse::<usize>(), col.parse::<usize>())) {
+            Some((Ok(row), Ok(col))) if (1..=self.size).contains(&row) && (1..=self.size).contains(&col) => {
+                Ok(self.position_of(row, col))
+            }
+            Some((Ok(_), Ok(_))) => Err(format!("Square '{}' is outside the grid", square)),
+            _ => Err(format!("Invalid square '{}'", square)),
+        }
+    }
+
+    // Name of a square in "r<row>c<col>" notation
+    pub fn name_of(&self, pos: usize) -> String {
+        format!("r{}c{}", self.row_of(pos), self.col_of(pos))
+    }
+
+    // Parses a list of squares separated by whitespace, e.g. "r1c1 r1c2 r2c3"
+    pub fn parse_squares(&self, squares: &str) -> Result<Vec<usize>, String> {
+        squares.split_whitespace().map(|square| self.parse_square(square)).collect()
+    }
+
+    // The first pair of consecutive squares in a path that don't touch each other, if any
+    pub fn gap_in(&self, path: &[usize]) -> Option<(usize, usize)> {
+        path.windows(2).find(|pair| !self.touching(pair[0], pair[1])).map(|pair| (pair[0], pair[1]))
+    }
+
+    // True if the squares share a side: they are next to each other in a row or a column
+    pub fn adjacent(&self, pos: usize, other: usize) -> bool {
+        self.row_of(pos).abs_diff(self.row_of(other)) + self.col_of(pos).abs_diff(self.col_of(other)) == 1
+    }
+
+    // True if the squares are different and touch each other horizontally, vertically or diagonally
+    pub fn touching(&self, pos: usize, other: usize) -> bool {
+        pos != other
+            && self.row_of(pos).abs_diff(self.row_of(other)) <= 1
+            && self.col_of(pos).abs_diff(self.col_of(other)) <= 1
+    }
+
+    // All units: first the rows, then the columns, then the boxes or jigsaw regions, then the extra units
+    pub fn units(&self) -> &[Vec<usize>] {
+        &self.units
+    }
+
+    // Indices (in units()) of the units the square belongs to
+    pub fn units_of(&self, pos: usize) -> &[usize] {
+        &self.units_of[pos]
+    }
+
+    // All other squares that share a unit with the square, or were added as extra peers
+    pub fn peers(&self, pos: usize) -> &[usize] {
+        &self.peers[pos]
+    }
+
+    // Pairs of squares that must have distinct values, on top of the units
+    pub fn extra_peers(&self) -> &[(usize, usize)] {
+        &self.extra_peers
+    }
+
+    // Adds an extra unit: the squares must contain each value exactly once
+    pub fn add_unit(&mut self, squares: Vec<usize>) {
+        assert_eq!(self.size, squares.len(), "A unit must contain {} squares", self.size);
+        self.units.push(squares);
+        self.connect_peers();
+    }
+
+    // X-Sudoku: both main diagonals must also contain every value exactly once
+    pub fn add_diagonals(&mut self) {
+        let size = self.size;
+        self.add_unit((1..=size).map(|i| self.position_of(i, i)).collect());
+        self.add_unit((1..=size).map(|i| self.position_of(i, size + 1 - i)).collect());
+    }
+
+    // Adds pairs of squares that must have distinct values, without being in the same unit
+    pub fn add_peers(&mut self, pairs: &[(usize, usize)]) {
+        self.extra_peers.extend(pairs.iter().filter(|(pos, other)| pos != other));
+        self.connect_peers();
+    }
+
+    // Anti-knight: squares a chess knight's move apart can't contain the same value
+    pub fn add_anti_knight(&mut self) {
+        self.add_chess_moves(&[(1, 2), (2, 1), (1, -2), (2, -1)]);
+    }
+
+    // Anti-king: squares a chess king's move apart (touching diagonally) can't contain the same value
+    pub fn add_anti_king(&mut self) {
+        self.add_chess_moves(&[(1, 1), (1, -1)]);
+    }
+
+    // Makes every square a peer of the squares at the given (row, column) offsets
+    fn add_chess_moves(&mut self, moves: &[(isize, isize)]) {
+        let mut pairs = Vec::new();
+        for pos in 0..self.cells() {
+            for (rows, cols) in moves.iter() {
+                let row = self.row_of(pos) as isize + rows;
+                let col = self.col_of(pos) as isize + cols;
+                if (1..=self.size as isize).contains(&row) && (1..=self.size as isize).contains(&col) {
+                    pairs.push((pos, self.position_of(row as usize, col as usize)));
+                }
+            }
+        }
+        self.add_peers(&pairs);
+    }
+
+    // Windoku (Hyper Sudoku): extra boxes, offset by one square from the boxes in the corners
+    // In a 9x9 grid, these are the 4 windows of 3x3 squares starting at (2,2), (2,6), (6,2) and (6,6)
+    pub fn add_windows(&mut self) {
+        assert!(!self.jigsaw, "Windows require regular boxes");
+        for row in self.window_starts(self.box_rows) {
+            for col in self.window_starts(self.box_cols) {
+                let mut window = Vec::new();
+                for r in row..row + self.box_rows {
+                    for c in col..col + self.box_cols {
+                        window.push(self.position_of(r, c));
+                    }
+                }
+                for pos in window.iter() {
+                    self.windows[*pos] = true;
+                }
+                self.add_unit(window);
+            }
+        }
+    }
+
+    // Windows start one square after the first box and are separated by one square
+    fn window_starts(&self, box_size: usize) -> Vec<usize> {
+        let mut starts = Vec::new();
+        let mut start = 2;
+        while start + box_size - 1 <= self.size {
+            starts.push(start);
+            start += box_size + 1;
+        }
+        starts
+    }
+
+    // True if the square is in one of the Windoku windows
+    pub fn in_window(&self, pos: usize) -> bool {
+        self.windows[pos]
+    }
+
+    // True if Windoku windows were added
+    pub fn has_windows(&self) -> bool {
+        self.windows.iter().any(|w| *w)
+    }
+
+    // Checks if all squares can be reached from the first one, moving horizontally or vertically within the squares
+    fn connected(&self, squares: &[usize]) -> bool {
+        let mut reached = vec![squares[0]];
+        let mut index = 0;
+        while index < reached.len() {
+            let pos = reached[index];
+            for other in squares.iter() {
+                if self.adjacent(pos, *other) && !reached.contains(other) {
+                    reached.push(*other);
+                }
+            }
+            index += 1;
+        }
+        reached.len() == squares.len()
+    }
+
+    fn connect_peers(&mut self) {
+        self.units_of = vec![Vec::new(); self.cells()];
+        self.peers = vec![Vec::new(); self.cells()];
+        for (index, unit) in self.units.iter().enumerate() {
+            for pos in unit.iter() {
+                self.units_of[*pos].push(index);
+                for peer in unit.iter() {
+                    if peer != pos {
+                        self.peers[*pos].push(*peer);
+                    }
+                }
+            }
+        }
+        for (pos, other) in self.extra_peers.iter() {
+            self.peers[*pos].push(*other);
+            self.peers[*other].push(*pos);
+        }
+        for peers in self.peers.iter_mut() {
+            peers.sort_unstable();
+            peers.dedup();
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::classic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classic_layout_has_27_units_and_20_peers() {
+        let layout = Layout::classic();
+        assert_eq!(9, layout.size());
+        assert_eq!(81, layout.cells());
+        assert_eq!(27, layout.units().len());
+        for pos in 0..81 {
+            assert_eq!(3, layout.units_of(pos).len());
+            assert_eq!(20, layout.peers(pos).len());
+        }
+        // Box in the middle of the grid
+        assert_eq!(
+            &vec![30, 31, 32, 39, 40, 41, 48, 49, 50],
+            &layout.units()[9 + 9 + 4]
+        );
+    }
+
+    #[test]
+    fn test_positions_are_row_by_row() {
+        let layout = Layout::new(2, 3);
+        assert_eq!(0, layout.position_of(1, 1));
+        assert_eq!(7, layout.position_of(2, 2));
+        assert_eq!(2, layout.row_of(7));
+        assert_eq!(2, layout.col_of(7));
+    }
+
+    #[test]
+    fn test_parse_squares_in_row_column_notation() {
+        let layout = Layout::classic();
+        assert_eq!(Ok(vec![0, 10, 80]), layout.parse_squares("r1c1 R2C2\tr9c9"));
+        assert_eq!(Err("Invalid square 'a1'".to_string()), layout.parse_square("a1"));
+        assert_eq!(Err("Invalid square 'r1'".to_string()), layout.parse_square("r1"));
+        assert_eq!(Err("Square 'r10c1' is outside the grid".to_string()), layout.parse_square("r10c1"));
+        assert_eq!("r2c3", layout.name_of(11));
+    }
+
+    #[test]
+    fn test_touching_and_adjacent_squares() {
+        let layout = Layout::classic();
+        assert!(layout.touching(0, 10));
+        assert!(!layout.touching(0, 2));
+        assert!(!layout.touching(8, 9));
+        assert!(layout.adjacent(0, 9));
+        assert!(!layout.adjacent(0, 10));
+        assert!(!layout.adjacent(8, 9));
+        assert_eq!(None, layout.gap_in(&[0, 10, 11, 1]));
+        assert_eq!(Some((11, 13)), layout.gap_in(&[0, 10, 11, 13]));
+    }
+
+    #[test]
+    fn test_boxes_of_6x6_grid_are_2_rows_by_3_columns() {
+        let layout = Layout::new(2, 3);
+        assert_eq!(6, layout.size());
+        assert_eq!(18, layout.units().len());
+        assert_eq!(&vec![0, 1, 2, 6, 7, 8], &layout.units()[12]);
+        assert_eq!(&vec![3, 4, 5, 9, 10, 11], &layout.units()[13]);
+        assert_eq!(&vec![12, 13, 14, 18, 19, 20], &layout.units()[14]);
+        // 5 in the row, 5 in the column, 2 more in the box
+        assert_eq!(12, layout.peers(0).len());
+        assert_eq!(1, layout.region_of(3));
+        assert_eq!(2, layout.region_of(12));
+        assert!(!layout.is_jigsaw());
+    }
+
+    #[test]
+    fn test_diagonals_are_extra_units() {
+        let mut layout = Layout::new(2, 2);
+        layout.add_diagonals();
+        assert_eq!(14, layout.units().len());
+        assert_eq!(&vec![0, 5, 10, 15], &layout.units()[12]);
+        assert_eq!(&vec![3, 6, 9, 12], &layout.units()[13]);
+        // Square (2,2) is on the main diagonal: (3,3) and (4,4) become peers
+        assert_eq!(&[1, 5, 8, 12], layout.units_of(5));
+        assert!(layout.peers(5).contains(&10));
+        assert!(layout.peers(5).contains(&15));
+        // Square (1,2) is on no diagonal
+        assert_eq!(3, layout.units_of(1).len());
+
+        let mut classic = Layout::classic();
+        classic.add_diagonals();
+        // The center is on both diagonals: 20 peers + 2 * 6 more outside its box
+        assert_eq!(32, classic.peers(40).len());
+    }
+
+    #[test]
+    fn test_chess_moves_add_extra_peers() {
+        let mut layout = Layout::classic();
+        layout.add_anti_knight();
+        // The center has 8 knight moves, all outside its row, column and box
+        assert_eq!(28, layout.peers(40).len());
+        assert!(layout.peers(40).contains(&(40 - 2 * 9 - 1)));
+        // A corner has only 2 knight moves, both inside its box
+        assert_eq!(20, layout.peers(0).len());
+        assert_eq!(27, layout.units().len());
+
+        let mut layout = Layout::classic();
+        layout.add_anti_king();
+        // The king's moves of the center stay in its box, from (3,3) 3 moves leave the box
+        assert_eq!(20, layout.peers(40).len());
+        assert_eq!(23, layout.peers(20).len());
+
+        // Extra units keep the extra peers
+        layout.add_diagonals();
+        assert!(layout.peers(20).contains(&(20 + 9 + 1)));
+        assert!(layout.peers(20).contains(&(20 + 9 - 1)));
+    }
+
+    #[test]
+    fn test_windows_are_extra_boxes() {
+        let mut layout = Layout::classic();
+        assert!(!layout.has_windows());
+        layout.add_windows();
+        assert!(layout.has_windows());
+        assert_eq!(31, layout.units().len());
+        assert_eq!(&vec![10, 11, 12, 19, 20, 21, 28, 29, 30], &layout.units()[27]);
+        assert_eq!(&vec![14, 15, 16, 23, 24, 25, 32, 33, 34], &layout.units()[28]);
+        assert_eq!(&vec![46, 47, 48, 55, 56, 57, 64, 65, 66], &layout.units()[29]);
+        assert_eq!(&vec![50, 51, 52, 59, 60, 61, 68, 69, 70], &layout.units()[30]);
+        assert!(layout.in_window(10));
+        assert!(!layout.in_window(13));
+        assert_eq!(36, (0..81).filter(|pos| layout.in_window(*pos)).count());
+
+        let mut small = Layout::new(2, 2);
+        small.add_windows();
+        assert_eq!(&vec![5, 6, 9, 10], &small.units()[12]);
+        assert_eq!(13, small.units().len());
+    }
+
+    #[test]
+    fn test_jigsaw_regions_replace_boxes() {
+        let layout = Layout::jigsaw("\naabb\nacbb\nacdd\nccdd\n").unwrap();
+        assert!(layout.is_jigsaw());
+        assert_eq!(4, layout.size());
+        assert_eq!(12, layout.units().len());
+        assert_eq!(&vec![0, 1, 4, 8], &layout.units()[8]);
+        assert_eq!(&vec![2, 3, 6, 7], &layout.units()[9]);
+        assert_eq!(&vec![5, 9, 12, 13], &layout.units()[10]);
+        assert_eq!(&vec![10, 11, 14, 15], &layout.units()[11]);
+        assert_eq!(3, layout.region_of(15));
+        // Row 1, column 1 and the rest of region a
+        assert_eq!(vec![1, 2, 3, 4, 8, 12], layout.peers(0).to_vec());
+    }
+
+    #[test]
+    fn test_invalid_jigsaw_regions_are_rejected() {
+        assert_eq!(
+            Err("Row 2 has 3 squares instead of 4".to_string()),
+            Layout::jigsaw("aabb\nacb\nacdd\nccdd").map(|_| ())
+        );
+        assert_eq!(
+            Err("Found 3 regions instead of 4".to_string()),
+            Layout::jigsaw("aabb\naabb\ncccc\ncccc").map(|_| ())
+        );
+        assert_eq!(
+            Err("Region 'c' has 5 squares instead of 4".to_string()),
+            Layout::jigsaw("aabb\nacbb\naccd\nccdd").map(|_| ())
+        );
+        assert_eq!(
+            Err("Region 'a' is not connected".to_string()),
+            Layout::jigsaw("abba\nabba\nccdd\nccdd").map(|_| ())
+        );
+    }
+}
@@ -1,3 +1,22 @@
+pub mod adjacency;
+pub mod arrow;
+pub mod constraint;
+pub mod difficulty;
 pub mod games;
+pub mod generator;
+pub mod inequality;
+pub mod killer;
+pub mod layout;
+pub mod lines;
+pub mod multi_grid;
+pub mod observer;
+pub mod random;
+pub mod sandwich;
+pub mod search_tree;
 pub mod square_value;
 pub mod sudoku;
+pub mod thermometer;
+#[cfg(feature = "serde")]
+pub mod trace;
+pub mod transform;
+pub mod variant;
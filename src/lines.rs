@@ -0,0 +1,364 @@
+// Cell and line constraints of variant puzzles
+// - Parity: shaded squares contain an even value, circled squares an odd value
+// - German whispers: neighbours along the line differ by at least 5 (in a 9x9 grid). So a 5 can't be on the line
+// - Renban: the line contains a set of consecutive values in any order, e.g. 4 2 3 5, without repeats
+// - Palindrome: the line reads the same in both directions, e.g. 3 7 1 7 3
+// - Between: the values on the line lie strictly between the values of its two ends (the circles)
+// - Little killer: the values on a diagonal, pointed at by a clue outside the grid, add up to the clue. Values may repeat
+//
+// Lines are paths of touching squares (horizontally, vertically or diagonally). See variant.rs for the text format
+
+use crate::adjacency::prune_pair;
+use crate::constraint::Constraint;
+use crate::sudoku::Board;
+
+pub struct Parity {
+    even: Vec<usize>,
+    odd: Vec<usize>,
+}
+
+impl Parity {
+    pub fn new(even: Vec<usize>, odd: Vec<usize>) -> Self {
+        Self { even, odd }
+    }
+}
+
+impl Constraint for Parity {
+    fn prune(&self, board: &mut Board) -> bool {
+        let mut pruned = false;
+        for value in 1..=board.size() {
+            let squares = if value % 2 == 0 { &self.odd } else { &self.even };
+            for pos in squares.iter() {
+                pruned |= board.eliminate(*pos, value);
+            }
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.even.iter().all(|pos| board.square_at(*pos).value() % 2 == 0)
+            && self.odd.iter().all(|pos| board.square_at(*pos).value() % 2 == 1)
+    }
+}
+
+pub struct Whisper {
+    squares: Vec<usize>,
+    difference: usize,
+}
+
+impl Whisper {
+    // Neighbours on the line differ by at least the given difference
+    pub fn new(squares: Vec<usize>, difference: usize) -> Self {
+        Self { squares, difference }
+    }
+
+    // German whispers: the difference is 5 in a 9x9 grid, or more generally half of the values, rounded up
+    pub fn german(squares: Vec<usize>, size: usize) -> Self {
+        Whisper::new(squares, (size + 1) / 2)
+    }
+
+    fn allows(&self, value: usize, other: usize) -> bool {
+        value.abs_diff(other) >= self.difference
+    }
+}
+
+impl Constraint for Whisper {
+    fn prune(&self, board: &mut Board) -> bool {
+        let mut pruned = false;
+        for pair in self.squares.windows(2) {
+            let allows = |value, other| self.allows(value, other);
+            pruned |= prune_pair(board, pair[0], pair[1], allows);
+            pruned |= prune_pair(board, pair[1], pair[0], allows);
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.squares
+            .windows(2)
+            .all(|pair| self.allows(board.square_at(pair[0]).value(), board.square_at(pair[1]).value()))
+    }
+}
+
+pub struct Renban {
+    squares: Vec<usize>,
+}
+
+impl Renban {
+    pub fn new(squares: Vec<usize>) -> Self {
+        Self { squares }
+    }
+}
+
+impl Constraint for Renban {
+    // Tries every run of consecutive values with the length of the line, e.g. 1..=3, 2..=4, ... for 3 squares
+    // A run is possible if every square can contain a value of the run, and every value of the run fits in a square
+    // The values of the line are distinct, so known values are removed from the other squares
+    fn prune(&self, board: &mut Board) -> bool {
+        let length = self.squares.len();
+        let candidates: Vec<Vec<usize>> = self.squares.iter().map(|pos| board.square_at(*pos).candidates()).collect();
+        let mut supported = vec![vec![false; board.size() + 1]; length];
+        for first in 1..=(board.size() + 1).saturating_sub(length) {
+            let run = first..first + length;
+            let squares_fit = candidates.iter().all(|values| values.iter().any(|v| run.contains(v)));
+            let values_fit = run.clone().all(|v| candidates.iter().any(|values| values.contains(&v)));
+            if squares_fit && values_fit {
+                for (index, values) in candidates.iter().enumerate() {
+                    for value in values.iter().filter(|v| run.contains(v)) {
+                        supported[index][*value] = true;
+                    }
+                }
+            }
+        }
+
+        let mut pruned = false;
+        for (index, pos) in self.squares.iter().enumerate() {
+            for value in candidates[index].iter() {
+                if !supported[index][*value] {
+                    pruned |= board.eliminate(*pos, *value);
+                }
+            }
+            let square = board.square_at(*pos);
+            if square.has_known_value() {
+                for other in self.squares.iter().filter(|other| *other != pos) {
+                    pruned |= board.eliminate(*other, square.value());
+                }
+            }
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        let mut values: Vec<usize> = self.squares.iter().map(|pos| board.square_at(*pos).value()).collect();
+        values.sort_unstable();
+        values.windows(2).all(|pair| pair[1] == pair[0] + 1)
+    }
+}
+
+pub struct Palindrome {
+    squares: Vec<usize>,
+}
+
+impl Palindrome {
+    pub fn new(squares: Vec<usize>) -> Self {
+        Self { squares }
+    }
+
+    // Pairs of squares at the same distance from both ends
+    fn mirrored(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.squares.iter().zip(self.squares.iter().rev()).take(self.squares.len() / 2).map(|(a, b)| (*a, *b))
+    }
+}
+
+impl Constraint for Palindrome {
+    // Mirrored squares contain the same value, so they keep the values that are possible in both
+    fn prune(&self, board: &mut Board) -> bool {
+        let mut pruned = false;
+        for (pos, other) in self.mirrored() {
+            for value in 1..=board.size() {
+                if !board.square_at(pos).can_have_value(value) || !board.square_at(other).can_have_value(value) {
+                    pruned |= board.eliminate(pos, value);
+                    pruned |= board.eliminate(other, value);
+                }
+            }
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.mirrored().all(|(pos, other)| board.square_at(pos).value() == board.square_at(other).value())
+    }
+}
+
+pub struct Between {
+    squares: Vec<usize>,
+}
+
+impl Between {
+    // The first and the last square are the circles, with at least one square in between
+    pub fn new(squares: Vec<usize>) -> Result<Self, String> {
+        if squares.len() < 3 {
+            return Err(format!("Between line needs at least 3 squares, found {}", squares.len()));
+        }
+        Ok(Self { squares })
+    }
+}
+
+impl Constraint for Between {
+    // Tries every pair of different values for the circles. The pair is possible if every square in between can
+    // contain a value strictly between them. Only the values of possible pairs are kept
+    fn prune(&self, board: &mut Board) -> bool {
+        let candidates: Vec<Vec<usize>> = self.squares.iter().map(|pos| board.square_at(*pos).candidates()).collect();
+        let last = candidates.len() - 1;
+        let mut supported = vec![vec![false; board.size() + 1]; candidates.len()];
+        for first_value in candidates[0].iter() {
+            for last_value in candidates[last].iter().filter(|v| *v != first_value) {
+                let (low, high) = (*first_value.min(last_value), *first_value.max(last_value));
+                let inside = |v: &&usize| low < **v && **v < high;
+                if candidates[1..last].iter().all(|values| values.iter().any(|v| inside(&v))) {
+                    supported[0][*first_value] = true;
+                    supported[last][*last_value] = true;
+                    for (index, values) in candidates.iter().enumerate().take(last).skip(1) {
+                        for value in values.iter().filter(inside) {
+                            supported[index][*value] = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut pruned = false;
+        for (index, pos) in self.squares.iter().enumerate() {
+            for value in candidates[index].iter() {
+                if !supported[index][*value] {
+                    pruned |= board.eliminate(*pos, *value);
+                }
+            }
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        let values: Vec<usize> = self.squares.iter().map(|pos| board.square_at(*pos).value()).collect();
+        let (first, last) = (values[0], values[values.len() - 1]);
+        let (low, high) = (first.min(last), first.max(last));
+        values[1..values.len() - 1].iter().all(|v| low < *v && *v < high)
+    }
+}
+
+pub struct LittleKiller {
+    squares: Vec<usize>,
+    sum: usize,
+}
+
+impl LittleKiller {
+    // The squares of the diagonal the clue points at
+    pub fn new(squares: Vec<usize>, sum: usize) -> Self {
+        Self { squares, sum }
+    }
+}
+
+impl Constraint for LittleKiller {
+    // Each square lies between the sum minus the highest and the sum minus the lowest possible total of the other squares
+    fn prune(&self, board: &mut Board) -> bool {
+        let size = board.size();
+        let lowest: Vec<usize> = self.squares.iter().map(|pos| board.square_at(*pos).value()).collect();
+        let highest: Vec<usize> = self.squares.iter().map(|pos| board.square_at(*pos).highest_value()).collect();
+        let lowest_sum: usize = lowest.iter().sum();
+        let highest_sum: usize = highest.iter().sum();
+        let mut pruned = false;
+        for (index, pos) in self.squares.iter().enumerate() {
+            let low = self.sum.saturating_sub(highest_sum - highest[index]).max(1);
+            let high = self.sum.saturating_sub(lowest_sum - lowest[index]).min(size);
+            pruned |= board.eliminate_outside(*pos, low, high);
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.squares.iter().map(|pos| board.square_at(*pos).value()).sum::<usize>() == self.sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parity_keeps_even_or_odd_values() {
+        let parity = Parity::new(vec![0], vec![1]);
+        let mut board = Board::new("parity", "");
+        assert!(parity.prune(&mut board));
+        assert_eq!(vec![2, 4, 6, 8], board.square_at(0).candidates());
+        assert_eq!(vec![1, 3, 5, 7, 9], board.square_at(1).candidates());
+        assert!(!parity.prune(&mut board));
+        assert!(parity.is_satisfied(&Board::new("ok", "21.......")));
+        assert!(!parity.is_satisfied(&Board::new("wrong", "12.......")));
+    }
+
+    #[test]
+    fn test_german_whisper_removes_5_and_close_values() {
+        let whisper = Whisper::german(vec![0, 1, 2], 9);
+        let mut board = Board::new("whisper", "");
+        assert!(whisper.prune(&mut board));
+        assert!(!board.square_at(1).can_have_value(5));
+        assert_eq!(8, board.square_at(1).possibilities());
+
+        // A 4 can only be next to a 9
+        let mut board = Board::new("whisper", ".4.......");
+        whisper.prune(&mut board);
+        assert_eq!(vec![9], board.square_at(0).candidates());
+        assert_eq!(vec![9], board.square_at(2).candidates());
+        assert!(whisper.is_satisfied(&Board::new("ok", "8179.....")));
+        assert!(!whisper.is_satisfied(&Board::new("wrong", "8149.....")));
+    }
+
+    #[test]
+    fn test_renban_keeps_runs_of_consecutive_values() {
+        let renban = Renban::new(vec![0, 1, 2]);
+        let mut board = Board::new("renban", "8........");
+        assert!(renban.prune(&mut board));
+        // Runs with an 8: 6..=8 and 7..=9
+        assert_eq!(vec![6, 7, 9], board.square_at(1).candidates());
+        assert_eq!(vec![6, 7, 9], board.square_at(2).candidates());
+
+        // A run needs every value in some square: without a 2, the line can't be 1..=3 or 2..=4
+        let mut board = Board::new("renban", "");
+        for pos in 0..3 {
+            board.eliminate(pos, 2);
+        }
+        board.eliminate(1, 1);
+        board.eliminate(2, 1);
+        renban.prune(&mut board);
+        assert_eq!(vec![3, 4, 5, 6, 7, 8, 9], board.square_at(0).candidates());
+    }
+
+    #[test]
+    fn test_renban_values_are_consecutive_in_any_order() {
+        let renban = Renban::new(vec![0, 1, 2, 3]);
+        assert!(renban.is_satisfied(&Board::new("ok", "4352.....")));
+        assert!(!renban.is_satisfied(&Board::new("gap", "4362.....")));
+    }
+
+    #[test]
+    fn test_palindrome_mirrors_possible_values() {
+        let palindrome = Palindrome::new(vec![0, 1, 10, 19, 20]);
+        let mut board = Board::new("palindrome", "3........");
+        assert!(palindrome.prune(&mut board));
+        assert_eq!(vec![3], board.square_at(20).candidates());
+        // The middle square has no mirror
+        assert_eq!(9, board.square_at(10).possibilities());
+        assert!(palindrome.is_satisfied(&Board::new("ok", "37.......\n.1.......\n.73......")));
+        assert!(!palindrome.is_satisfied(&Board::new("wrong", "37.......\n.1.......\n.74......")));
+    }
+
+    #[test]
+    fn test_between_line_keeps_values_inside_the_circles() {
+        let between = Between::new(vec![0, 1, 2, 3]).unwrap();
+        let mut board = Board::new("between", "");
+        assert!(between.prune(&mut board));
+        // Two different values fit between the circles, so a circle can't be 2..8 at both ends at the same time
+        assert_eq!(vec![2, 3, 4, 5, 6, 7, 8], board.square_at(1).candidates());
+        assert!(board.square_at(0).can_have_value(1));
+
+        let mut board = Board::new("between", "3..7.....");
+        between.prune(&mut board);
+        assert_eq!(vec![4, 5, 6], board.square_at(1).candidates());
+        assert!(between.is_satisfied(&Board::new("ok", "2548.....")));
+        assert!(!between.is_satisfied(&Board::new("wrong", "2598.....")));
+        assert_eq!(Err("Between line needs at least 3 squares, found 2".to_string()), Between::new(vec![0, 1]).map(|_| ()));
+        assert!(Between::new(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_little_killer_bounds_the_diagonal() {
+        // Two squares that add up to 4
+        let little_killer = LittleKiller::new(vec![0, 10], 4);
+        let mut board = Board::new("little killer", "");
+        assert!(little_killer.prune(&mut board));
+        assert_eq!(vec![1, 2, 3], board.square_at(0).candidates());
+        assert_eq!(vec![1, 2, 3], board.square_at(10).candidates());
+        assert!(little_killer.is_satisfied(&Board::new("ok", "1........\n.3.......")));
+        assert!(!little_killer.is_satisfied(&Board::new("wrong", "1........\n.2.......")));
+    }
+}
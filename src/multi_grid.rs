@@ -0,0 +1,303 @@
+// A multi-grid sudoku consists of several overlapping grids, e.g. a Samurai Sudoku: five 9x9 grids where the
+// center grid shares each of its corner boxes with one of the four outer grids
+//
+// Every grid is a Board, placed at an offset in one big grid. Squares of different grids at the same place in the
+// big grid are shared: they have the same value. The puzzle is solved as a whole:
+// - Each grid applies its own solving techniques
+// - The possible values of shared squares are intersected, so deductions in one grid reach the overlapping grids
+// - Repeat until no more progress is made, then guess a value and solve the resulting puzzle (depth-first)
+
+use crate::square_value::symbol_of;
+use crate::sudoku::Board;
+use std::fmt;
+
+// The squares of the grids that are at the same place in the big grid
+type SharedSquares = Vec<(usize, usize)>;
+
+#[derive(Clone)]
+pub struct MultiGrid {
+    pub name: String,
+    grids: Vec<Board>,
+    // (row, col) of the top left square of each grid in the big grid, 0-based
+    offsets: Vec<(usize, usize)>,
+    shared: Vec<SharedSquares>,
+}
+
+impl MultiGrid {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            grids: Vec::new(),
+            offsets: Vec::new(),
+            shared: Vec::new(),
+        }
+    }
+
+    // Offsets of the five 9x9 grids of a Samurai Sudoku: the four corners and the center
+    const SAMURAI: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+    const SAMURAI_SIZE: usize = 21;
+
+    // Parses a Samurai Sudoku in the standard 21x21 text layout: 21 lines of squares, using spaces where
+    // there are no grids. Each grid is read like a regular sudoku: digits are given values, '.' or '0' are empty. E.g.
+    // 1.......4   ..3....8.
+    // ...
+    // ......5.......2......
+    // ...
+    // Empty lines are skipped
+    pub fn samurai(name: &str, squares: &str) -> Result<Self, String> {
+        let lines: Vec<Vec<char>> = squares
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.chars().collect())
+            .collect();
+        if lines.len() != MultiGrid::SAMURAI_SIZE {
+            return Err(format!("Found {} lines instead of {}", lines.len(), MultiGrid::SAMURAI_SIZE));
+        }
+        let mut result = MultiGrid::new(name);
+        for (index, (top, left)) in MultiGrid::SAMURAI.iter().enumerate() {
+            let mut initial = String::new();
+            for (row, line) in lines[*top..top + 9].iter().enumerate() {
+                if line.len() < left + 9 {
+                    return Err(format!("Line {} is too short for grid {}", top + row + 1, index + 1));
+                }
+                initial.extend(line[*left..left + 9].iter());
+                initial.push('\n');
+            }
+            let grid = Board::new(&format!("{} {}", name, index + 1), &initial);
+            result.add_grid(grid, *top, *left);
+        }
+        Ok(result)
+    }
+
+    // Places a grid with its top left square at (row, col) of the big grid, 0-based
+    pub fn add_grid(&mut self, grid: Board, row: usize, col: usize) {
+        let index = self.grids.len();
+        let size = grid.size();
+        for pos in 0..size * size {
+            let place = (row + pos / size, col + pos % size);
+            let overlapping: Vec<(usize, usize)> = self
+                .grids
+                .iter()
+                .enumerate()
+                .filter_map(|(other, other_grid)| {
+                    MultiGrid::position_in(other_grid, self.offsets[other], place).map(|other_pos| (other, other_pos))
+                })
+                .collect();
+            for square in overlapping {
+                self.add_shared(square, (index, pos));
+            }
+        }
+        self.grids.push(grid);
+        self.offsets.push((row, col));
+    }
+
+    // Position of a square of the big grid in a grid, if the grid covers it
+    fn position_in(grid: &Board, offset: (usize, usize), place: (usize, usize)) -> Option<usize> {
+        let size = grid.size();
+        if place.0 >= offset.0 && place.0 < offset.0 + size && place.1 >= offset.1 && place.1 < offset.1 + size {
+            Some((place.0 - offset.0) * size + place.1 - offset.1)
+        } else {
+            None
+        }
+    }
+
+    fn add_shared(&mut self, square: (usize, usize), other: (usize, usize)) {
+        match self.shared.iter_mut().find(|shared| shared.contains(&square)) {
+            Some(shared) if shared.contains(&other) => {}
+            Some(shared) => shared.push(other),
+            None => self.shared.push(vec![square, other]),
+        }
+    }
+
+    pub fn grids(&self) -> &[Board] {
+        &self.grids
+    }
+
+    // Groups of squares, as (grid, position), that are shared between grids
+    pub fn shared(&self) -> &[SharedSquares] {
+        &self.shared
+    }
+
+    pub fn solved(&self) -> bool {
+        self.grids.iter().all(|grid| grid.solved())
+    }
+
+    pub fn contains_contradiction(&self) -> bool {
+        self.grids.iter().any(|grid| grid.contains_contradiction())
+    }
+
+    pub fn possibilities(&self) -> usize {
+        self.grids.iter().map(|grid| grid.possibilities()).sum()
+    }
+
+    // Number of levels of guesses that were needed
+    pub fn depth(&self) -> usize {
+        self.grids.iter().map(|grid| grid.depth).max().unwrap_or(0)
+    }
+
+    // Returns true if the puzzle was solved
+    pub fn solve(&mut self) -> bool {
+        self.solve_without_guessing();
+        if self.solved() {
+            return true;
+        }
+        if self.contains_contradiction() {
+            return false;
+        }
+        let (grid, pos) = match self.find_square_to_guess() {
+            Some(square) => square,
+            None => return false,
+        };
+        for value in self.grids[grid].square_at(pos).candidates() {
+            let mut experiment = self.clone();
+            experiment.set_value(grid, pos, value);
+            if experiment.solve() {
+                *self = experiment;
+                return true;
+            }
+        }
+        false
+    }
+
+    // Solves every grid and shares the results, until no more progress is made
+    fn solve_without_guessing(&mut self) {
+        let mut possibilities = self.possibilities() + 1;
+        while self.possibilities() < possibilities && !self.contains_contradiction() {
+            possibilities = self.possibilities();
+            for grid in self.grids.iter_mut() {
+                grid.solve();
+            }
+            self.share_possible_values();
+        }
+    }
+
+    // Every shared square keeps only the values that are possible in all grids it belongs to
+    fn share_possible_values(&mut self) {
+        for shared in self.shared.clone().iter() {
+            for value in 1..=self.grids[shared[0].0].size() {
+                if shared.iter().any(|(grid, pos)| !self.grids[*grid].square_at(*pos).can_have_value(value)) {
+                    for (grid, pos) in shared.iter() {
+                        self.grids[*grid].eliminate(*pos, value);
+                    }
+                }
+            }
+        }
+    }
+
+    // Sets the value of a square, in every grid it belongs to
+    fn set_value(&mut self, grid: usize, pos: usize, value: usize) {
+        let squares = match self.shared.iter().find(|shared| shared.contains(&(grid, pos))) {
+            Some(shared) => shared.clone(),
+            None => vec![(grid, pos)],
+        };
+        for (grid, pos) in squares {
+            self.grids[grid].eliminate_outside(pos, value, value);
+        }
+    }
+
+    // The unknown square with the fewest possible values, over all grids
+    fn find_square_to_guess(&self) -> Option<(usize, usize)> {
+        let mut squares = Vec::new();
+        for (index, grid) in self.grids.iter().enumerate() {
+            for pos in 0..grid.size() * grid.size() {
+                if !grid.square_at(pos).has_known_value() {
+                    squares.push((index, pos));
+                }
+            }
+        }
+        squares
+            .into_iter()
+            .min_by_key(|(grid, pos)| self.grids[*grid].square_at(*pos).possibilities())
+    }
+}
+
+// Prints the big grid in the same layout as the input, with spaces where there are no grids
+impl fmt::Display for MultiGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = self.grids.iter().zip(self.offsets.iter()).map(|(grid, (row, _))| row + grid.size()).max().unwrap_or(0);
+        let cols = self.grids.iter().zip(self.offsets.iter()).map(|(grid, (_, col))| col + grid.size()).max().unwrap_or(0);
+        let mut output = String::new();
+        output += "\n";
+        for row in 0..rows {
+            let mut line = String::new();
+            for col in 0..cols {
+                let square = self.grids.iter().zip(self.offsets.iter()).find_map(|(grid, offset)| {
+                    MultiGrid::position_in(grid, *offset, (row, col)).map(|pos| grid.square_at(pos))
+                });
+                line.push(match square {
+                    Some(square) if square.has_known_value() => symbol_of(square.value()),
+                    Some(_) => '.',
+                    None => ' ',
+                });
+            }
+            output += line.trim_end();
+            output += "\n";
+        }
+        write!(f, "{}", output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::*;
+
+    #[test]
+    fn test_samurai_grids_share_corner_boxes() {
+        let samurai = MultiGrid::samurai("samurai", samurai_sudoku()).unwrap();
+        assert_eq!(5, samurai.grids().len());
+        assert_eq!("samurai 3", samurai.grids()[2].name);
+        // 4 boxes of 9 squares, each shared by the center grid and one outer grid
+        assert_eq!(36, samurai.shared().len());
+        // Top left square of the bottom right box of the top left grid is the top left square of the center grid
+        assert!(samurai.shared().contains(&vec![(0, 60), (2, 0)]));
+        assert!(samurai.shared().contains(&vec![(2, 80), (4, 20)]));
+        // Given values are read from the right place
+        assert_eq!(9, samurai.grids()[0].square(1, 6).value());
+        assert_eq!(6, samurai.grids()[1].square(1, 6).value());
+        assert_eq!(2, samurai.grids()[2].square(4, 6).value());
+    }
+
+    #[test]
+    fn test_samurai_is_printed_in_the_input_layout() {
+        let samurai = MultiGrid::samurai("samurai", samurai_sudoku()).unwrap();
+        assert_eq!(samurai_sudoku(), samurai.to_string());
+    }
+
+    #[test]
+    fn test_invalid_samurai_is_rejected() {
+        let lines: Vec<&str> = samurai_sudoku().lines().filter(|line| !line.is_empty()).collect();
+        assert_eq!(
+            Err("Found 20 lines instead of 21".to_string()),
+            MultiGrid::samurai("short", &lines[1..].join("\n")).map(|_| ())
+        );
+        let mut lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        lines[3].truncate(15);
+        assert_eq!(
+            Err("Line 4 is too short for grid 2".to_string()),
+            MultiGrid::samurai("narrow", &lines.join("\n")).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_deductions_reach_overlapping_grid() {
+        // Only the top left grid has values, except in the box it shares with the center grid
+        // Completing that box in the top left grid also completes it in the center grid
+        let mut squares: Vec<String> = samurai_sudoku_solution()
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.chars().map(|c| if c == ' ' { ' ' } else { '.' }).collect())
+            .collect();
+        for (row, line) in samurai_sudoku_solution().lines().filter(|line| !line.is_empty()).take(9).enumerate() {
+            let known = if row < 6 { 9 } else { 6 };
+            squares[row].replace_range(0..known, &line[0..known]);
+        }
+        let mut samurai = MultiGrid::samurai("top left", &squares.join("\n")).unwrap();
+        assert!(!samurai.grids()[2].square(1, 1).has_known_value());
+        samurai.solve_without_guessing();
+        assert!(samurai.grids()[0].solved());
+        assert_eq!(samurai.grids()[0].square(7, 7).value(), samurai.grids()[2].square(1, 1).value());
+        assert_eq!(samurai.grids()[0].square(9, 9).value(), samurai.grids()[2].square(3, 3).value());
+        assert!(!samurai.grids()[2].solved());
+    }
+}
@@ -0,0 +1,174 @@
+// An Observer is notified of every step the solver takes
+// Events are typed, so observers decide for themselves what to do with them. Nothing is formatted unless an observer asks for it
+// Each notification comes with the board on which the step was taken, so the depth and a snapshot (Debug output) are available on demand
+// Positions in events are 1-based (row, col), the same as SquareValue
+
+use crate::sudoku::Board;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(tag = "event"))]
+pub enum SolveEvent {
+    // The solving techniques are about to be applied to the board
+    Solving,
+    // The known value of a square was removed from the possibilities of its row, column and subgrid
+    ValuePropagated { row: usize, col: usize, value: usize },
+    // A square was the only one in a row, column or subgrid that could hold the value
+    SingletonPromoted { row: usize, col: usize, value: usize },
+    // Two squares in the same row, column or subgrid can only hold the same two values
+    NakedPairFound {
+        first: (usize, usize),
+        second: (usize, usize),
+        value1: usize,
+        value2: usize,
+    },
+    // The solver is stuck and tries an experimental board where the square has the value
+    GuessMade { row: usize, col: usize, value: usize },
+    // A square has no possible values left. The board is abandoned
+    Contradiction,
+    // The guess that created the experimental board led to a contradiction, so the search continues with other experiments
+    Backtracked { row: usize, col: usize, value: usize },
+    // All squares have a known value
+    Solved,
+}
+
+pub trait Observer {
+    fn notify(&mut self, board: &Board, event: &SolveEvent);
+}
+
+// Any closure that accepts a board and an event can be used as observer, so observers can capture state
+impl<F: FnMut(&Board, &SolveEvent)> Observer for F {
+    fn notify(&mut self, board: &Board, event: &SolveEvent) {
+        self(board, event)
+    }
+}
+
+// Observer that formats events as text and sends them to a simple output function, such as println!
+pub struct Logger {
+    output: fn(&str),
+}
+
+impl Logger {
+    pub fn new(output: fn(&str)) -> Self {
+        Self { output }
+    }
+
+    fn describe(board: &Board, event: &SolveEvent) -> String {
+        match event {
+            SolveEvent::Solving => format!("Solving board at depth {} {:?} ", board.depth, board),
+            SolveEvent::ValuePropagated { row, col, value } => {
+                format!("Propagating value {} of ({},{})", value, row, col)
+            }
+            SolveEvent::SingletonPromoted { row, col, value } => {
+                format!("Promoting singleton {} at ({},{})", value, row, col)
+            }
+            SolveEvent::NakedPairFound { first, second, value1, value2 } => format!(
+                "Cleaning naked pair {} and {} at ({},{}) and ({},{})",
+                value1, value2, first.0, first.1, second.0, second.1
+            ),
+            SolveEvent::GuessMade { row, col, value } => format!(
+                ">>> Guess that square ({},{}) has value {} at level {}",
+                row, col, value, board.depth
+            ),
+            SolveEvent::Contradiction => format!(
+                "!! Board results in contradiction. Backtracking from level {} !!",
+                board.depth
+            ),
+            SolveEvent::Backtracked { row, col, value } => format!(
+                "<<< Guess that square ({},{}) has value {} was wrong at level {}",
+                row, col, value, board.depth
+            ),
+            SolveEvent::Solved => format!("Solved board at level {} {:?}", board.depth, board),
+        }
+    }
+}
+
+impl Observer for Logger {
+    fn notify(&mut self, board: &Board, event: &SolveEvent) {
+        let output = self.output;
+        output(&Logger::describe(board, event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::*;
+    use crate::sudoku::Game;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_closure_observer_captures_events() {
+        let events: Rc<RefCell<Vec<SolveEvent>>> = Rc::new(RefCell::new(Vec::new()));
+        let captured = events.clone();
+        let mut game = Game::new("easy", easy_sudoku());
+        game.observer(Rc::new(RefCell::new(move |_: &Board, event: &SolveEvent| {
+            captured.borrow_mut().push(*event)
+        })));
+        game.solve();
+
+        let events = events.borrow();
+        assert_eq!(Some(&SolveEvent::Solving), events.first());
+        assert_eq!(Some(&SolveEvent::Solved), events.last());
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, SolveEvent::ValuePropagated { row: 1, col: 4, value: 2 })));
+    }
+
+    #[test]
+    fn test_logger_describes_events() {
+        let board = Board::new("easy", easy_sudoku());
+        assert_eq!(
+            "Propagating value 2 of (1,4)",
+            Logger::describe(&board, &SolveEvent::ValuePropagated { row: 1, col: 4, value: 2 })
+        );
+        assert_eq!(
+            "Cleaning naked pair 3 and 8 at (2,3) and (2,6)",
+            Logger::describe(
+                &board,
+                &SolveEvent::NakedPairFound { first: (2, 3), second: (2, 6), value1: 3, value2: 8 }
+            )
+        );
+        assert_eq!(
+            ">>> Guess that square (5,5) has value 7 at level 0",
+            Logger::describe(&board, &SolveEvent::GuessMade { row: 5, col: 5, value: 7 })
+        );
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        guesses: usize,
+        contradictions: usize,
+        backtracks: usize,
+        deepest: usize,
+    }
+
+    impl Observer for Counter {
+        fn notify(&mut self, board: &Board, event: &SolveEvent) {
+            match event {
+                SolveEvent::GuessMade { .. } => self.guesses += 1,
+                SolveEvent::Contradiction => self.contradictions += 1,
+                SolveEvent::Backtracked { .. } => self.backtracks += 1,
+                _ => {}
+            }
+            self.deepest = self.deepest.max(board.depth);
+        }
+    }
+
+    #[test]
+    fn test_observer_sees_guesses_and_contradictions() {
+        let counter = Rc::new(RefCell::new(Counter::default()));
+        let mut game = Game::new("not fun", not_fun_sudoku1());
+        game.observer(counter.clone());
+        game.solve();
+
+        assert!(game.solved());
+        let counter = counter.borrow();
+        assert!(counter.guesses > 0);
+        assert!(counter.contradictions > 0);
+        assert_eq!(counter.contradictions, counter.backtracks);
+        assert_eq!(game.depth(), counter.deepest);
+    }
+}
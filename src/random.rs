@@ -0,0 +1,77 @@
+// A small pseudo random number generator, so puzzles can be generated without extra dependencies
+// The same seed always gives the same sequence of numbers, so generated puzzles are reproducible
+//
+// The state is initialised with SplitMix64 (which also turns seed 0 into a usable state), numbers are generated
+// with xorshift64*. Good enough for shuffling squares and values, not for cryptography
+
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        let mut mixed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^= mixed >> 31;
+        Self {
+            state: if mixed == 0 { 1 } else { mixed },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // A number in 0..limit
+    pub fn below(&mut self, limit: usize) -> usize {
+        (self.next_u64() % limit as u64) as usize
+    }
+
+    // Puts the items in a random order (Fisher-Yates)
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            let other = self.below(index + 1);
+            items.swap(index, other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_gives_same_numbers() {
+        let mut first = Random::new(42);
+        let mut second = Random::new(42);
+        let mut other = Random::new(43);
+        let numbers: Vec<u64> = (0..5).map(|_| first.next_u64()).collect();
+        assert_eq!(numbers, (0..5).map(|_| second.next_u64()).collect::<Vec<u64>>());
+        assert_ne!(numbers, (0..5).map(|_| other.next_u64()).collect::<Vec<u64>>());
+        assert_ne!(0, Random::new(0).next_u64());
+    }
+
+    #[test]
+    fn test_numbers_stay_below_limit() {
+        let mut random = Random::new(7);
+        let mut seen = [false; 9];
+        for _ in 0..200 {
+            seen[random.below(9)] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+    }
+
+    #[test]
+    fn test_shuffle_keeps_all_items() {
+        let mut items: Vec<usize> = (1..=9).collect();
+        Random::new(3).shuffle(&mut items);
+        assert_ne!((1..=9).collect::<Vec<usize>>(), items);
+        items.sort_unstable();
+        assert_eq!((1..=9).collect::<Vec<usize>>(), items);
+    }
+}
@@ -0,0 +1,214 @@
+// Sandwich Sudoku: a clue outside a row or column gives the sum of the values between the 1 and the 9 in that line
+// E.g. in the row 3 1 5 2 9 4 6 7 8 the sandwich is 5 + 2 = 7. When the 1 and the 9 are next to each other, the sum is 0
+// In other grid sizes, the highest value takes the place of the 9
+//
+// Pruning tries every placement of the 1 and the 9 in a line that the possible values allow:
+// - the squares between them must contain distinct values 2..8 that add up to the clue (see the Killer cage combinations)
+// - the squares outside them can't contain 1 or 9
+// Only values that are part of at least one feasible placement are kept
+
+use crate::constraint::Constraint;
+use crate::killer::find_combinations;
+use crate::layout::Layout;
+use crate::sudoku::Board;
+
+// The sum between the 1 and the 9 in a row or column
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Clue {
+    pub squares: Vec<usize>,
+    pub sum: usize,
+}
+
+pub struct Sandwich {
+    clues: Vec<Clue>,
+}
+
+impl Sandwich {
+    pub fn new(clues: Vec<Clue>) -> Self {
+        Self { clues }
+    }
+
+    // Parses the clues of the rows and the columns, one line each, a clue per line of the grid from top to bottom
+    // or left to right. A '.' means there is no clue for that line. E.g.
+    // rows: 10 . 25 0 . . 35 . 4
+    // columns: . 2 . . 17 . . 8 .
+    pub fn parse(clues: &str, layout: &Layout) -> Result<Self, String> {
+        let mut result = Vec::new();
+        for line in clues.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let (kind, sums) = match line.split_once(':') {
+                Some((kind, sums)) => (kind.trim(), sums.split_whitespace().collect::<Vec<&str>>()),
+                None => return Err(format!("Unexpected line '{}'", line)),
+            };
+            let units = match kind {
+                "rows" => &layout.units()[0..layout.size()],
+                "columns" => &layout.units()[layout.size()..2 * layout.size()],
+                _ => return Err(format!("Unexpected line '{}'", line)),
+            };
+            if sums.len() != layout.size() {
+                return Err(format!("Found {} {} clues instead of {}", sums.len(), kind, layout.size()));
+            }
+            for (squares, sum) in units.iter().zip(sums.iter()) {
+                if *sum != "." {
+                    let sum = sum.parse::<usize>().map_err(|_| format!("Invalid sandwich clue '{}'", sum))?;
+                    result.push(Clue {
+                        squares: squares.clone(),
+                        sum,
+                    });
+                }
+            }
+        }
+        Ok(Sandwich::new(result))
+    }
+
+    pub fn clues(&self) -> &[Clue] {
+        &self.clues
+    }
+}
+
+impl Constraint for Sandwich {
+    fn prune(&self, board: &mut Board) -> bool {
+        let mut pruned = false;
+        for clue in self.clues.iter() {
+            pruned |= prune_line(board, clue);
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.clues.iter().all(|clue| {
+            let values: Vec<usize> = clue.squares.iter().map(|pos| board.square_at(*pos).value()).collect();
+            let lowest = values.iter().position(|v| *v == 1);
+            let highest = values.iter().position(|v| *v == board.size());
+            match (lowest, highest) {
+                (Some(lowest), Some(highest)) => {
+                    let (first, last) = (lowest.min(highest), lowest.max(highest));
+                    values[first + 1..last].iter().sum::<usize>() == clue.sum
+                }
+                _ => false,
+            }
+        })
+    }
+}
+
+// Removes the values that are not part of any feasible placement of the 1 and the 9
+fn prune_line(board: &mut Board, clue: &Clue) -> bool {
+    let size = board.size();
+    let candidates: Vec<Vec<usize>> = clue.squares.iter().map(|pos| board.square_at(*pos).candidates()).collect();
+    let mut supported = vec![vec![false; size + 1]; clue.squares.len()];
+    for lowest in 0..candidates.len() {
+        for highest in 0..candidates.len() {
+            if lowest != highest && candidates[lowest].contains(&1) && candidates[highest].contains(&size) {
+                support_placement(&candidates, clue.sum, lowest, highest, &mut supported);
+            }
+        }
+    }
+
+    let mut pruned = false;
+    for (index, pos) in clue.squares.iter().enumerate() {
+        for value in candidates[index].iter() {
+            if !supported[index][*value] {
+                pruned |= board.eliminate(*pos, *value);
+            }
+        }
+    }
+    pruned
+}
+
+// Marks the values that fit with the 1 at square lowest and the 9 at square highest, if the sandwich can add up to the sum
+fn support_placement(candidates: &[Vec<usize>], sum: usize, lowest: usize, highest: usize, supported: &mut [Vec<bool>]) {
+    let size = supported[0].len() - 1;
+    let (first, last) = (lowest.min(highest), lowest.max(highest));
+    let filling: Vec<Vec<usize>> = candidates[first + 1..last]
+        .iter()
+        .map(|values| values.iter().copied().filter(|v| *v != 1 && *v != size).collect())
+        .collect();
+    let mut filling_supported = vec![vec![false; size + 1]; filling.len()];
+    find_combinations(&filling, sum, &mut Vec::new(), &mut filling_supported);
+    // Every value of a combination is marked, so the first square has a marked value if there is any combination
+    let feasible = match filling_supported.first() {
+        Some(values) => values.iter().any(|v| *v),
+        None => sum == 0,
+    };
+    if !feasible {
+        return;
+    }
+    supported[lowest][1] = true;
+    supported[highest][size] = true;
+    for (index, values) in filling_supported.iter().enumerate() {
+        for (value, possible) in values.iter().enumerate() {
+            supported[first + 1 + index][value] |= *possible;
+        }
+    }
+    for index in (0..first).chain(last + 1..candidates.len()) {
+        for value in candidates[index].iter().filter(|v| **v != 1 && **v != size) {
+            supported[index][*value] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_clues() {
+        let layout = Layout::classic();
+        let sandwich = Sandwich::parse("rows: 10 . . . . . . . 0\ncolumns: . 35 . . . . . . .\n", &layout).unwrap();
+        assert_eq!(3, sandwich.clues().len());
+        assert_eq!(Clue { squares: (0..9).collect(), sum: 10 }, sandwich.clues()[0]);
+        assert_eq!(Clue { squares: (72..81).collect(), sum: 0 }, sandwich.clues()[1]);
+        assert_eq!(Clue { squares: (0..9).map(|row| row * 9 + 1).collect(), sum: 35 }, sandwich.clues()[2]);
+    }
+
+    #[test]
+    fn test_invalid_clues_are_rejected() {
+        let layout = Layout::classic();
+        let error = |clues| Sandwich::parse(clues, &layout).map(|_| ()).unwrap_err();
+        assert_eq!("Found 8 rows clues instead of 9", error("rows: 1 2 3 4 5 6 7 8"));
+        assert_eq!("Invalid sandwich clue 'x'", error("columns: 1 2 3 4 5 6 7 8 x"));
+        assert_eq!("Unexpected line 'diagonals: 1'", error("diagonals: 1"));
+        assert_eq!("Unexpected line '1 2 3'", error("1 2 3"));
+    }
+
+    #[test]
+    fn test_zero_sum_puts_1_and_9_together() {
+        let sandwich = Sandwich::new(vec![Clue { squares: (0..9).collect(), sum: 0 }]);
+        let mut board = Board::new("zero", "1........");
+        board.solve();
+        assert!(sandwich.prune(&mut board));
+        assert_eq!(vec![9], board.square_at(1).candidates());
+        assert!(!board.square_at(2).can_have_value(9));
+    }
+
+    #[test]
+    fn test_large_sum_keeps_1_and_9_apart() {
+        // 2 + 3 + ... + 8 = 35: the 1 and the 9 are at both ends
+        let sandwich = Sandwich::new(vec![Clue { squares: (0..9).collect(), sum: 35 }]);
+        let mut board = Board::new("35", "");
+        assert!(sandwich.prune(&mut board));
+        assert_eq!(vec![1, 9], board.square_at(0).candidates());
+        assert_eq!(vec![1, 9], board.square_at(8).candidates());
+        assert_eq!(vec![2, 3, 4, 5, 6, 7, 8], board.square_at(4).candidates());
+    }
+
+    #[test]
+    fn test_sum_limits_the_sandwich() {
+        // A sandwich of 5 is 5 or 2 + 3. With the 1 in the first square, the 9 is in the third or fourth square
+        let sandwich = Sandwich::new(vec![Clue { squares: (0..9).collect(), sum: 5 }]);
+        let mut board = Board::new("5", "1........");
+        board.solve();
+        sandwich.prune(&mut board);
+        assert_eq!(vec![2, 3, 5], board.square_at(1).candidates());
+        assert!(board.square_at(2).can_have_value(9));
+        assert!(board.square_at(3).can_have_value(9));
+        assert!(!board.square_at(4).can_have_value(9));
+        assert!(!board.square_at(1).can_have_value(9));
+    }
+
+    #[test]
+    fn test_is_satisfied_checks_the_sum() {
+        let sandwich = Sandwich::new(vec![Clue { squares: (0..9).collect(), sum: 7 }]);
+        assert!(sandwich.is_satisfied(&Board::new("ok", "315294678")));
+        assert!(!sandwich.is_satisfied(&Board::new("wrong", "315249678")));
+    }
+}
@@ -0,0 +1,213 @@
+// The search tree records the guesses made by Game::solve
+// The root is the initial board. Every experiment (board + 1 guessed value) is a child of the board it was generated from
+// Each node remembers the outcome of applying the solving techniques to its board, so different
+// search strategies can be compared visually by exporting the tree in Graphviz DOT format:
+//   dot -Tsvg tree.dot > tree.svg
+
+use crate::sudoku::Board;
+use std::fmt::Write;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    // The experiment was generated, but the search ended before it was tried
+    Pending,
+    // The board could not be solved without guessing, so new experiments were generated from it
+    Expanded,
+    // The board ended up with a square without possible values
+    Contradiction,
+    // The board was solved
+    Solved,
+}
+
+impl Outcome {
+    // Outcome of a board after the solving techniques have been applied
+    pub fn of(board: &Board) -> Outcome {
+        Outcome::after(board.solved(), board)
+    }
+
+    // The same, when the solving techniques already told whether they solved the board
+    pub fn after(solved: bool, board: &Board) -> Outcome {
+        if solved {
+            Outcome::Solved
+        } else if board.contains_contradiction() {
+            Outcome::Contradiction
+        } else {
+            Outcome::Expanded
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            Outcome::Pending => "white",
+            Outcome::Expanded => "lightblue",
+            Outcome::Contradiction => "lightcoral",
+            Outcome::Solved => "palegreen",
+        }
+    }
+}
+
+// A guess that square (row, col) has value
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Guess {
+    pub row: usize,
+    pub col: usize,
+    pub value: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SearchNode {
+    pub parent: Option<usize>,
+    pub guess: Option<Guess>,
+    pub depth: usize,
+    // Number of values that were possible for the guessed square = number of siblings
+    pub branching: usize,
+    pub outcome: Outcome,
+}
+
+pub struct SearchTree {
+    nodes: Vec<SearchNode>,
+}
+
+impl SearchTree {
+    // Identifier of the root node, representing the initial board
+    pub const ROOT: usize = 0;
+
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![SearchNode {
+                parent: None,
+                guess: None,
+                depth: 0,
+                branching: 1,
+                outcome: Outcome::Pending,
+            }],
+        }
+    }
+
+    // Adds a guess below the parent node and returns the identifier of the new node
+    pub fn add(&mut self, parent: usize, guess: Guess, depth: usize, branching: usize) -> usize {
+        self.nodes.push(SearchNode {
+            parent: Some(parent),
+            guess: Some(guess),
+            depth,
+            branching,
+            outcome: Outcome::Pending,
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn set_outcome(&mut self, node: usize, outcome: Outcome) {
+        self.nodes[node].outcome = outcome;
+    }
+
+    pub fn nodes(&self) -> &[SearchNode] {
+        &self.nodes
+    }
+
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.nodes.iter().filter(|n| n.outcome == outcome).count()
+    }
+
+    // Graphviz DOT representation of the tree. Nodes are colored by outcome
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut output = String::new();
+        writeln!(output, "digraph \"{}\" {{", name.replace('"', "\\\"")).unwrap();
+        writeln!(output, "  node [shape=box, style=filled];").unwrap();
+        for (id, node) in self.nodes.iter().enumerate() {
+            let label = match node.guess {
+                Some(guess) => format!(
+                    "({},{}) = {}\\nlevel {}",
+                    guess.row, guess.col, guess.value, node.depth
+                ),
+                None => "start".to_string(),
+            };
+            writeln!(
+                output,
+                "  n{} [label=\"{}\", fillcolor={}];",
+                id,
+                label,
+                node.outcome.color()
+            )
+            .unwrap();
+        }
+        for (id, node) in self.nodes.iter().enumerate() {
+            if let Some(parent) = node.parent {
+                writeln!(output, "  n{} -> n{};", parent, id).unwrap();
+            }
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
+impl Default for SearchTree {
+    fn default() -> Self {
+        SearchTree::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::*;
+    use crate::sudoku::Game;
+
+    #[test]
+    fn test_tree_is_exported_as_dot() {
+        let mut tree = SearchTree::new();
+        tree.set_outcome(SearchTree::ROOT, Outcome::Expanded);
+        let wrong = tree.add(SearchTree::ROOT, Guess { row: 1, col: 2, value: 3 }, 1, 2);
+        let right = tree.add(SearchTree::ROOT, Guess { row: 1, col: 2, value: 4 }, 1, 2);
+        tree.set_outcome(wrong, Outcome::Contradiction);
+        tree.set_outcome(right, Outcome::Solved);
+
+        let dot = tree.to_dot("example");
+        assert!(dot.starts_with("digraph \"example\" {\n"));
+        assert!(dot.contains("  n0 [label=\"start\", fillcolor=lightblue];\n"));
+        assert!(dot.contains("  n1 [label=\"(1,2) = 3\\nlevel 1\", fillcolor=lightcoral];\n"));
+        assert!(dot.contains("  n2 [label=\"(1,2) = 4\\nlevel 1\", fillcolor=palegreen];\n"));
+        assert!(dot.contains("  n0 -> n1;\n  n0 -> n2;\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_outcome_of_boards() {
+        assert_eq!(Outcome::Solved, Outcome::of(&Board::new("solved", easy_sudoku_solution())));
+        assert_eq!(Outcome::Contradiction, Outcome::of(&Board::new("repeated", "1.......1")));
+        assert_eq!(Outcome::Expanded, Outcome::of(&Board::new("easy", easy_sudoku())));
+        assert_eq!(Outcome::Contradiction, Outcome::after(false, &Board::new("repeated", "1.......1")));
+    }
+
+    #[test]
+    fn test_game_records_search_tree() {
+        let mut game = Game::new("not fun", not_fun_sudoku1());
+        game.record_search_tree();
+        game.solve();
+
+        let tree = game.search_tree().unwrap();
+        assert_eq!(Outcome::Expanded, tree.nodes()[SearchTree::ROOT].outcome);
+        assert_eq!(1, tree.count(Outcome::Solved));
+        assert!(tree.count(Outcome::Contradiction) > 0);
+        let solved = tree.nodes().iter().find(|n| n.outcome == Outcome::Solved).unwrap();
+        assert_eq!(game.depth(), solved.depth);
+        for node in tree.nodes().iter().skip(1) {
+            let parent = tree.nodes()[node.parent.unwrap()];
+            assert_eq!(Outcome::Expanded, parent.outcome);
+            assert_eq!(parent.depth + 1, node.depth);
+        }
+    }
+
+    #[test]
+    fn test_search_tree_is_only_recorded_on_request() {
+        let mut game = Game::new("easy", easy_sudoku());
+        game.solve();
+        assert!(game.search_tree().is_none());
+
+        let mut game = Game::new("easy", easy_sudoku());
+        game.record_search_tree();
+        game.solve();
+        let tree = game.search_tree().unwrap();
+        assert_eq!(1, tree.nodes().len());
+        assert_eq!(Outcome::Solved, tree.nodes()[SearchTree::ROOT].outcome);
+    }
+}
@@ -1,31 +1,50 @@
-// A Square Value can contain a number 1..9
+// A Square Value can contain a number 1..size, where size is 9 for a classic sudoku
 // Each boolean in possible_values indicates if the corresponding value can be in the cell
-// We start with assuming that any of the 1..9 values is possible and reduce the possibilities with constraints
+// We start with assuming that any of the 1..size values is possible and reduce the possibilities with constraints
+// Values above 9 are shown as letters: 10 = 'A', 11 = 'B', ... 16 = 'G', ... 25 = 'P'
 
 use std::fmt;
 use std::ops::RangeInclusive;
 
 #[derive(Copy, Clone)]
 pub struct SquareValue {
-    possible_values: [bool; 9],
+    possible_values: [bool; SquareValue::MAX_VALUES],
+    size: usize,
     pub row: usize,
     pub col: usize,
     propagated: bool,
 }
 
 impl SquareValue {
+    pub const MAX_VALUES: usize = 25;
+
     pub fn new() -> Self {
+        SquareValue::with_size(9)
+    }
+
+    pub fn with_size(size: usize) -> Self {
+        let mut possible_values = [false; SquareValue::MAX_VALUES];
+        // By default, every value is possible
+        for possible in possible_values.iter_mut().take(size) {
+            *possible = true;
+        }
         Self {
-            // By default, every value is possible
-            possible_values: [true; 9],
+            possible_values,
+            size,
             row: 0,
             col: 0,
             propagated: false,
         }
     }
 
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
     // Value is 1-base, array of possible values is 0-based
-    const ALL_VALUES: RangeInclusive<usize> = 1..=9;
+    pub fn all_values(&self) -> RangeInclusive<usize> {
+        1..=self.size
+    }
 
     fn position_of_value(value: usize) -> usize {
         value - 1
@@ -36,7 +55,7 @@ impl SquareValue {
         self.col = col;
     }
 
-    // Returns true if only one of the 9 possibilities is available
+    // Returns true if only one of the size possibilities is available
     pub fn has_known_value(&self) -> bool {
         self.possibilities() == 1
     }
@@ -46,7 +65,7 @@ impl SquareValue {
     }
 
     pub fn possibilities(&self) -> usize {
-        self.possible_values.iter().filter(|v| **v).count()
+        self.possible_values[..self.size].iter().filter(|v| **v).count()
     }
 
     // Set the possibilities so that only the given value is possible. Sets the initial given values
@@ -60,7 +79,7 @@ impl SquareValue {
     // Return the number contained in the value
     // If more than one value is still possible (!has_known_value), returns the lowest possible value
     pub fn value(&self) -> usize {
-        for v in SquareValue::ALL_VALUES {
+        for v in self.all_values() {
             if self.possible_values[SquareValue::position_of_value(v)] {
                 return v;
             }
@@ -68,11 +87,35 @@ impl SquareValue {
         0
     }
 
+    // The highest value that is still possible, 0 if no value is possible
+    pub fn highest_value(&self) -> usize {
+        self.all_values().rev().find(|v| self.can_have_value(*v)).unwrap_or(0)
+    }
+
+    // All values that are still possible, in increasing order
+    pub fn candidates(&self) -> Vec<usize> {
+        self.all_values()
+            .filter(|v| self.can_have_value(*v))
+            .collect()
+    }
+
     // Remove possible value because another square in the same row/column/subgrid already has the value to maintain distinct constraint
     pub fn cant_have_value(&mut self, value: usize) {
         self.possible_values[SquareValue::position_of_value(value)] = false;
     }
 
+    // Remove all possible values outside lowest..=highest. Returns true if any value was removed
+    pub fn keep_values_between(&mut self, lowest: usize, highest: usize) -> bool {
+        let mut removed = false;
+        for v in self.all_values() {
+            if (v < lowest || v > highest) && self.can_have_value(v) {
+                self.cant_have_value(v);
+                removed = true;
+            }
+        }
+        removed
+    }
+
     // Check if the given value is still possible
     pub fn can_have_value(&self, value: usize) -> bool {
         self.possible_values[SquareValue::position_of_value(value)]
@@ -87,14 +130,24 @@ impl SquareValue {
     pub fn has_been_propagated(&mut self) {
         self.propagated = true;
     }
+}
 
-    // Determine coordinates 0..=2 of the subgrid the square is in
-    pub fn row_grid(&self) -> usize {
-        (self.row - 1) / 3
+// The character that represents a value: '1'..'9', followed by 'A'..'P' for 10..25
+pub fn symbol_of(value: usize) -> char {
+    if value <= 9 {
+        std::char::from_digit(value as u32, 10).unwrap()
+    } else {
+        (b'A' + (value - 10) as u8) as char
     }
+}
 
-    pub fn col_grid(&self) -> usize {
-        (self.col - 1) / 3
+// The value represented by a character, if any. Lowercase letters are accepted too
+pub fn value_of(symbol: char) -> Option<usize> {
+    match symbol {
+        '1'..='9' => symbol.to_digit(10).map(|d| d as usize),
+        'A'..='P' => Some(symbol as usize - 'A' as usize + 10),
+        'a'..='p' => Some(symbol as usize - 'a' as usize + 10),
+        _ => None,
     }
 }
 
@@ -114,9 +167,9 @@ impl fmt::Debug for SquareValue {
         } else {
             '.'
         };
-        for value in 1..=9 {
+        for value in self.all_values() {
             if self.can_have_value(value) {
-                output.push_str(&value.to_string());
+                output.push(symbol_of(value));
             } else {
                 output.push(placeholder);
             }
@@ -172,6 +225,21 @@ mod tests {
         assert_eq!(false, value.needs_to_be_propagated());
     }
 
+    #[test]
+    fn test_keeping_a_range_of_values() {
+        let mut value = SquareValue::new();
+        value.cant_have_value(4);
+
+        assert!(value.keep_values_between(3, 6));
+        assert_eq!(vec![3, 5, 6], value.candidates());
+        assert_eq!(3, value.value());
+        assert_eq!(6, value.highest_value());
+        assert!(!value.keep_values_between(2, 7));
+
+        assert!(value.keep_values_between(7, 9));
+        assert_eq!(0, value.highest_value());
+    }
+
     #[test]
     fn test_reducing_possibilities_due_to_constraints() {
         let mut value = SquareValue::new();
@@ -188,6 +256,36 @@ mod tests {
 
         assert!(value.has_known_value());
         assert_eq!(7, value.value());
+        assert_eq!(vec![7], value.candidates());
+    }
+
+    #[test]
+    fn test_larger_squares_use_letters() {
+        let mut value = SquareValue::with_size(16);
+        assert_eq!(16, value.possibilities());
+        assert_eq!("123456789ABCDEFG", format!("{:?}", value));
+
+        value.set_known_value(12);
+        assert_eq!(12, value.value());
+        assert_eq!("___________C____", format!("{:?}", value));
+
+        let small = SquareValue::with_size(4);
+        assert_eq!(vec![1, 2, 3, 4], small.candidates());
+    }
+
+    #[test]
+    fn test_symbols_and_values() {
+        assert_eq!('1', symbol_of(1));
+        assert_eq!('9', symbol_of(9));
+        assert_eq!('A', symbol_of(10));
+        assert_eq!('G', symbol_of(16));
+        assert_eq!('P', symbol_of(25));
+        for value in 1..=25 {
+            assert_eq!(Some(value), value_of(symbol_of(value)));
+        }
+        assert_eq!(Some(11), value_of('b'));
+        assert_eq!(None, value_of('.'));
+        assert_eq!(None, value_of('0'));
     }
 
     #[test]
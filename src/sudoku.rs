@@ -1,21 +1,40 @@
-use crate::square_value::SquareValue;
+use crate::constraint::{default_constraints, Constraint};
+use crate::layout::Layout;
+use crate::observer::{Logger, Observer, SolveEvent};
+use crate::random::Random;
+use crate::search_tree::{Guess, Outcome, SearchTree};
+use crate::square_value::{symbol_of, value_of, SquareValue};
+use std::cell::RefCell;
 use std::fmt;
-use std::ops::RangeInclusive;
+use std::rc::Rc;
 
-// A sudoku game has a name and 9x9 squares with values
+// A sudoku game has a name and squares with values, arranged according to a layout. By default 9x9 squares in 3x3 subgrids
+// The board starts with the classic rules of the layout as constraints, see default_constraints. They check the known
+// values, the solving techniques below enforce them on the units and peers of the layout, so they don't prune
+// The layout and the constraints (e.g. Killer cages) are shared between a board and all experimental boards cloned from it
 // The depth indicates how many levels of 'guesses' were needed to find solution. 0 = no guessing
-// You can optionally provide a logger function to output intermediate steps
-// logging flag allows for quick check if logging is enabled so that we don't pay the overhead of formatting output
+// You can optionally provide an observer to follow intermediate steps
+// The observer is shared between a board and all experimental boards cloned from it
+// With a random number generator, guesses are tried in random order. It is shared like the observer, so that the
+// same seed always gives the same search
 pub struct Board {
     pub name: String,
-    values: [SquareValue; 81],
+    layout: Rc<Layout>,
+    values: Vec<SquareValue>,
+    // The given values of the puzzle, 0 for a square without a given value
+    initial: Rc<Vec<usize>>,
     pub depth: usize,
-    report: fn(&str),
-    logging: bool,
+    // The default constraints come first, followed by the extra constraints
+    constraints: Rc<Vec<Rc<dyn Constraint>>>,
+    defaults: usize,
+    observer: Option<Rc<RefCell<dyn Observer>>>,
+    random: Option<Rc<RefCell<Random>>>,
 }
 
+// Optionally, the game records the tree of guesses made while solving
 pub struct Game {
     board: Board,
+    search_tree: Option<SearchTree>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -26,20 +45,33 @@ pub struct NakedPair {
     value2: usize,
 }
 
+// node identifies the experiment in the search tree, if one is recorded
+// rank orders the guesses for the same square: the value itself, or its place in random order
 pub struct Experimental {
     pub value: usize,
+    pub rank: usize,
     pub branching: usize,
     pub trying: SquareValue,
     pub board: Board,
+    pub node: usize,
 }
 
 impl Experimental {
     pub fn new(board: Board, trying: SquareValue, value: usize, branching: usize) -> Self {
-        Self { value, branching, trying, board }
+        Self { value, rank: value, branching, trying, board, node: SearchTree::ROOT }
     }
 
     pub fn order(&self) -> usize {
-        self.board.depth * 10000 + self.branching * 1000 + (self.trying.row * self.trying.col) + self.value
+        self.board.depth * 1_000_000 + self.branching * 10_000 + (self.trying.row * self.trying.col) + self.rank
+    }
+
+    // Experiments are only reported as guesses when they are tried, not when they are generated
+    fn notify_guess(&self) {
+        self.board.notify(SolveEvent::GuessMade {
+            row: self.trying.row,
+            col: self.trying.col,
+            value: self.value,
+        });
     }
 }
 
@@ -47,16 +79,65 @@ impl Game {
     pub fn new(game_name: &str, initial: &str) -> Self {
         Self {
             board: Board::new(game_name, initial),
+            search_tree: None,
         }
     }
 
+    // A game with another grid size, e.g. Layout::new(2, 3) for a 6x6 sudoku with subgrids of 2 rows and 3 columns
+    pub fn with_layout(game_name: &str, initial: &str, layout: Layout) -> Self {
+        Self {
+            board: Board::with_layout(game_name, initial, layout),
+            search_tree: None,
+        }
+    }
+
+    // A game that starts from the possible values of a board, e.g. one that is already partly solved
+    pub fn from_board(board: Board) -> Self {
+        Self {
+            board,
+            search_tree: None,
+        }
+    }
+
+    // Record the tree of guesses during solve, so it can be inspected or exported afterwards
+    pub fn record_search_tree(&mut self) {
+        self.search_tree = Some(SearchTree::new());
+    }
+
+    pub fn search_tree(&self) -> Option<&SearchTree> {
+        self.search_tree.as_ref()
+    }
+
     // Provide a logger function for intermediate steps
     pub fn logger(&mut self, output: fn(&str)) {
         self.board.logger(output);
     }
 
+    // Add an extra rule the solution must satisfy, e.g. Killer cages
+    pub fn add_constraint(&mut self, constraint: Rc<dyn Constraint>) {
+        self.board.add_constraint(constraint);
+    }
+
+    // Provide an observer that receives every solving step as a typed event
+    pub fn observer(&mut self, observer: Rc<RefCell<dyn Observer>>) {
+        self.board.observer(observer);
+    }
+
+    // Guess values in random order, so a puzzle with several solutions can end up in any of them
+    pub fn randomize(&mut self, seed: u64) {
+        self.board.randomize(seed);
+    }
+
     pub fn solve(&mut self) -> bool {
-        let solved = self.board.solve();
+        self.solve_with(Board::solve)
+    }
+
+    // The search of solve, with another way to solve each board, e.g. with the techniques in another order
+    pub(crate) fn solve_with(&mut self, solve: fn(&mut Board) -> bool) -> bool {
+        let solved = solve(&mut self.board);
+        if self.search_tree.is_some() {
+            self.record_outcome(SearchTree::ROOT, Outcome::after(solved, &self.board));
+        }
         if !solved {
             // Last resort: guess a value and recurse
             // heuristic: guess squares with the least number of possibilities, so as to maximize odds of guessing right
@@ -68,14 +149,27 @@ impl Game {
             // - An experiment that results in inconsistencies is dropped
             // - The list of experiments is kept sorted so that the experiments with smallest depth are at the back, to be popped and tried
             let mut experiments = self.board.generate_experiments();
+            self.record_experiments(SearchTree::ROOT, &mut experiments);
             experiments.sort_by(|a, b| b.order().partial_cmp(&a.order()).unwrap());
             while !self.board.solved() && !experiments.is_empty() {
                 if let Some(mut experimental) = experiments.pop() {
-                    if experimental.board.solve() {
+                    experimental.notify_guess();
+                    let solved = solve(&mut experimental.board);
+                    let outcome = Outcome::after(solved, &experimental.board);
+                    self.record_outcome(experimental.node, outcome);
+                    if outcome == Outcome::Solved {
                         self.board.assign(&experimental.board);
                         return true;
-                    } else if !experimental.board.contains_contradiction() {
-                        experiments.append(&mut experimental.board.generate_experiments());
+                    } else if outcome == Outcome::Contradiction {
+                        experimental.board.notify(SolveEvent::Backtracked {
+                            row: experimental.trying.row,
+                            col: experimental.trying.col,
+                            value: experimental.value,
+                        });
+                    } else {
+                        let mut generated = experimental.board.generate_experiments();
+                        self.record_experiments(experimental.node, &mut generated);
+                        experiments.append(&mut generated);
                         experiments.sort_by(|a, b| b.order().partial_cmp(&a.order()).unwrap());
                     }
                 }
@@ -84,6 +178,39 @@ impl Game {
         self.board.solved()
     }
 
+    fn record_outcome(&mut self, node: usize, outcome: Outcome) {
+        if let Some(tree) = &mut self.search_tree {
+            tree.set_outcome(node, outcome);
+        }
+    }
+
+    fn record_experiments(&mut self, parent: usize, experiments: &mut [Experimental]) {
+        if let Some(tree) = &mut self.search_tree {
+            for experimental in experiments.iter_mut() {
+                let guess = Guess {
+                    row: experimental.trying.row,
+                    col: experimental.trying.col,
+                    value: experimental.value,
+                };
+                experimental.node = tree.add(parent, guess, experimental.board.depth, experimental.branching);
+            }
+        }
+    }
+
+    // Number of solutions, counting stops at limit. A proper puzzle has exactly 1
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.board.count_solutions(limit)
+    }
+
+    pub fn is_minimal(&self) -> bool {
+        self.board.is_minimal()
+    }
+
+    // Removes the redundant given values and returns them
+    pub fn minimize(&mut self) -> Vec<SquareValue> {
+        self.board.minimize()
+    }
+
     pub fn solved(&self) -> bool {
         self.board.solved()
     }
@@ -105,39 +232,42 @@ impl Clone for Board {
     fn clone(&self) -> Board {
         Self {
             name: self.name.clone(),
-            values: self.values,
+            layout: self.layout.clone(),
+            values: self.values.clone(),
+            initial: self.initial.clone(),
             depth: self.depth + 1,
-            report: self.report,
-            logging: self.logging,
+            constraints: self.constraints.clone(),
+            defaults: self.defaults,
+            observer: self.observer.clone(),
+            random: self.random.clone(),
         }
     }
 }
 
-// checks for self.logging are separate for readability
-#[allow(clippy::collapsible_else_if)]
-#[allow(clippy::collapsible_if)]
 impl Board {
-    const ALL_VALUES: RangeInclusive<usize> = 1..=9;
-    const ALL_ROWS: RangeInclusive<usize> = 1..=9;
-    const ALL_COLUMNS: RangeInclusive<usize> = 1..=9;
-
-    // Default empty logger implementation
-    fn silent(_str: &str) {}
-
     pub fn new(game_name: &str, initial: &str) -> Self {
+        Board::with_layout(game_name, initial, Layout::classic())
+    }
+
+    pub fn with_layout(game_name: &str, initial: &str, layout: Layout) -> Self {
+        let size = layout.size();
+        let constraints = default_constraints(&layout);
+        let parsed = parse_initial_sudoku_values(initial, size);
         let mut result = Self {
             name: game_name.to_string(),
-            values: [SquareValue::new(); 81],
+            values: vec![SquareValue::with_size(size); layout.cells()],
+            initial: Rc::new(parsed.clone()),
+            layout: Rc::new(layout),
             depth: 0,
-            report: Board::silent,
-            logging: false,
+            defaults: constraints.len(),
+            constraints: Rc::new(constraints),
+            observer: None,
+            random: None,
         };
-        for row in Board::ALL_ROWS {
-            for col in Board::ALL_COLUMNS {
-                result.values[Board::position_of(row, col)].at(row, col);
-            }
+        for pos in 0..result.layout.cells() {
+            let (row, col) = (result.layout.row_of(pos), result.layout.col_of(pos));
+            result.values[pos].at(row, col);
         }
-        let parsed = parse_initial_sudoku_values(initial);
         for (pos, parsed_value) in parsed.iter().enumerate() {
             if *parsed_value != 0 {
                 result.values[pos].set_known_value(*parsed_value);
@@ -146,144 +276,314 @@ impl Board {
         result
     }
 
+    // A random solved classic board: the same seed gives the same board
+    pub fn random_solved(seed: u64) -> Option<Self> {
+        Board::random_solved_with_layout(seed, Layout::classic())
+    }
+
+    // The guesses pick a random value among the values that are still possible. That's not exactly uniform over all
+    // solved boards, but close: every value shows up about as often in every square
+    // The search is depth-first: breadth-first, like Game::solve, keeps far too many boards around for an empty grid
+    // None if the layout has no solution at all, e.g. because of too many extra peers
+    pub fn random_solved_with_layout(seed: u64, layout: Layout) -> Option<Self> {
+        let mut board = Board::with_layout("random", "", layout);
+        board.randomize(seed);
+        let mut solution = board.first_solution()?;
+        solution.depth = 0;
+        solution.random = None;
+        Some(solution)
+    }
+
+    // Guess values in random order, so a puzzle with several solutions can end up in any of them
+    pub fn randomize(&mut self, seed: u64) {
+        self.random = Some(Rc::new(RefCell::new(Random::new(seed))));
+    }
+
     // Provide a logger function for intermediate steps
     pub fn logger(&mut self, output: fn(&str)) {
-        self.report = output;
-        self.logging = true;
+        self.observer(Rc::new(RefCell::new(Logger::new(output))));
     }
 
-    fn report(&self, str: String) {
-        let f = self.report;
-        f(&str);
+    // Add an extra rule the solution must satisfy, e.g. Killer cages
+    pub fn add_constraint(&mut self, constraint: Rc<dyn Constraint>) {
+        Rc::make_mut(&mut self.constraints).push(constraint);
     }
 
-    fn assign(&mut self, other: &Board) {
-        self.values = other.values;
-        self.depth = other.depth;
+    // Provide an observer that receives every solving step as a typed event
+    pub fn observer(&mut self, observer: Rc<RefCell<dyn Observer>>) {
+        self.observer = Some(observer);
     }
 
-    fn position_of(row: usize, col: usize) -> usize {
-        (row - 1) * 9 + (col - 1)
+    fn notify(&self, event: SolveEvent) {
+        if let Some(observer) = &self.observer {
+            observer.borrow_mut().notify(self, &event);
+        }
     }
 
-    pub fn row_of(pos: usize) -> usize { pos / 9 + 1 }
-    pub fn col_of(pos: usize) -> usize { pos % 9 + 1 }
-    pub fn rowgrid_of(pos: usize) -> usize { (pos / 9) / 3 }
-    pub fn colgrid_of(pos: usize) -> usize { (pos % 9) / 3 }
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
 
-    pub fn possibilities(&self) -> usize {
-        self.values.iter().map(|c| c.possibilities()).sum()
+    // Number of values, rows and columns
+    pub fn size(&self) -> usize {
+        self.layout.size()
     }
 
-    pub fn solved(&self) -> bool {
-        self.values.iter().all(|c| c.has_known_value())
+    // The square at the given 1-based position
+    pub fn square(&self, row: usize, col: usize) -> SquareValue {
+        self.values[self.layout.position_of(row, col)]
     }
 
-    pub fn contains_contradiction(&self) -> bool {
-        self.values.iter().any(|c| c.is_contradiction())
+    // The square at the given position 0..N*N, see Layout
+    pub fn square_at(&self, pos: usize) -> SquareValue {
+        self.values[pos]
     }
 
-    fn all_values_in_row(row: usize) -> [usize; 9] {
-        let mut result = [0; 9];
+    // Removes a possible value from a square. Returns true if the value was possible
+    pub fn eliminate(&mut self, pos: usize, value: usize) -> bool {
+        let possible = self.values[pos].can_have_value(value);
+        self.values[pos].cant_have_value(value);
+        possible
+    }
 
-        for col in Board::ALL_ROWS {
-            result[col - 1] = Board::position_of(row, col);
-        }
+    // Removes all possible values outside lowest..=highest from a square. Returns true if any value was removed
+    pub fn eliminate_outside(&mut self, pos: usize, lowest: usize, highest: usize) -> bool {
+        self.values[pos].keep_values_between(lowest, highest)
+    }
 
-        result
+    fn assign(&mut self, other: &Board) {
+        self.values = other.values.clone();
+        self.depth = other.depth;
     }
 
-    fn all_values_in_column(col: usize) -> [usize; 9] {
-        let mut result = [0; 9];
+    pub fn possibilities(&self) -> usize {
+        self.values.iter().map(|c| c.possibilities()).sum()
+    }
 
-        for row in Board::ALL_ROWS {
-            result[row - 1] = Board::position_of(row, col);
-        }
-        result
+    pub fn solved(&self) -> bool {
+        self.values.iter().all(|c| c.has_known_value()) && !self.violates_constraints()
     }
 
-    fn all_values_in_subgrid(rowgrid: usize, colgrid: usize) -> [usize; 9] {
-        let mut result = [0; 9];
+    // A board is contradictory if a square has no possible values left, or if the known values break a constraint,
+    // e.g. peers have the same known value
+    pub fn contains_contradiction(&self) -> bool {
+        self.values.iter().any(|c| c.is_contradiction()) || self.violates_constraints()
+    }
 
-        let row_offset = rowgrid * 3;
-        let col_offset = colgrid * 3;
+    // The extra constraints, added after the classic rules of the layout
+    fn extra_constraints(&self) -> &[Rc<dyn Constraint>] {
+        &self.constraints[self.defaults..]
+    }
 
-        let mut pos = 0;
-        for r in 1..=3 {
-            for c in 1..=3 {
-                result[pos] = Board::position_of(row_offset + r, col_offset + c);
-                pos += 1;
-            }
+    // Propagating a known value removes it from all its peers, also from a peer with the same known value, which
+    // becomes a contradiction. So the default constraints can only be broken while known values still need to be
+    // propagated
+    fn violates_constraints(&self) -> bool {
+        let constraints = if self.values.iter().any(|c| c.needs_to_be_propagated()) {
+            &self.constraints[..]
+        } else {
+            self.extra_constraints()
+        };
+        if constraints.is_empty() {
+            return false;
+        }
+        if self.values.iter().all(|c| c.has_known_value()) {
+            !constraints.iter().all(|c| c.is_satisfied(self))
+        } else {
+            !constraints.iter().all(|c| c.is_consistent(self))
         }
-
-        result
     }
 
     // Sudoku solver returns
     // true -> solution found
     // false -> no solution found
     pub fn solve(&mut self) -> bool {
-        if self.logging {
-            self.report(format!("Solving board at depth {} {:?} ", self.depth, self));
-        }
+        self.notify(SolveEvent::Solving);
         // As long as we're making progress, apply our solving techniques
         let mut progress_made = true;
         let mut pairs: Vec<NakedPair> = Vec::new();
         while progress_made {
             // Technique 1: propagate unit values to reduce possibilities in same row, column and subgrid
-            progress_made = self.propagate_all_known_values();
-            if progress_made {
-                if self.logging {
-                    self.report(format!("After propagating known values {:?}", self));
-                }
-                if self.solved() {
-                    return true;
-                }
-                if self.contains_contradiction() {
-                    if self.logging {
-                        self.report(format!("!! Board results in contradiction. Backtracking from level {} !!", self.depth));
-                    }
-                    return false;
-                }
+            // Propagation goes on until there is nothing left to propagate, so another round is only needed if the
+            // other techniques make progress
+            self.propagate_all_known_values();
+            if self.solved() {
+                self.notify(SolveEvent::Solved);
+                return true;
+            }
+            if self.contains_contradiction() {
+                self.notify(SolveEvent::Contradiction);
+                return false;
+            }
 
-                progress_made = self.find_naked_pairs(&mut pairs);
-                if progress_made {
-                    if self.logging {
-                        self.report(format!("After cleaning up naked pairs {:?}", self));
-                    }
-                }
+            progress_made = self.find_naked_pairs(&mut pairs);
 
-                // Technique 2: possibilities may have been reduced so that 'singletons' can be found
-                // When a singleton is promoted to value, this value must be propagated
-                progress_made |= self.promote_singletons();
-                if progress_made {
-                    if self.logging {
-                        self.report(format!("After promoting singletons {:?}", self));
-                    }
-                }
+            // Technique 2: possibilities may have been reduced so that 'singletons' can be found
+            // When a singleton is promoted to value, this value must be propagated
+            progress_made |= self.promote_singletons();
+
+            // Technique 3: let the extra constraints remove values that can't satisfy them
+            if !self.extra_constraints().is_empty() {
+                progress_made |= self.apply_constraints();
             }
         }
+        if self.contains_contradiction() {
+            self.notify(SolveEvent::Contradiction);
+        }
         false
     }
 
+    // Number of solutions, counting stops at limit
+    // Unlike Game::solve, the search is depth-first and doesn't stop at the first solution
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        let mut board = self.clone();
+        board.solve();
+        if board.contains_contradiction() {
+            return 0;
+        }
+        if board.solved() {
+            return 1;
+        }
+        let mut count = 0;
+        for experimental in board.generate_experiments() {
+            experimental.notify_guess();
+            count += experimental.board.count_solutions(limit - count);
+            if count >= limit {
+                break;
+            }
+        }
+        count
+    }
+
+    // Depth-first search for a solution, trying the experiments in the order they were generated
+    fn first_solution(&self) -> Option<Board> {
+        let mut board = self.clone();
+        board.solve();
+        if board.contains_contradiction() {
+            return None;
+        }
+        if board.solved() {
+            return Some(board);
+        }
+        board.generate_experiments().into_iter().find_map(|experimental| {
+            experimental.notify_guess();
+            experimental.board.first_solution()
+        })
+    }
+
+    // A puzzle is minimal if it has a single solution, and removing any given value would allow more solutions
+    // Only the given values of the puzzle count, not the values the solver found
+    pub fn is_minimal(&self) -> bool {
+        let givens = self.givens();
+        self.with_givens(&givens).count_solutions(2) == 1
+            && (0..givens.len()).all(|index| {
+                let others: Vec<usize> = givens.iter().enumerate().filter(|(i, _)| *i != index).map(|(_, pos)| *pos).collect();
+                self.with_givens(&others).count_solutions(2) > 1
+            })
+    }
+
+    // Removes given values one by one, as long as the puzzle keeps a single solution. The result is minimal
+    // Returns the removed squares with their values. A puzzle without a single solution is left alone
+    // If any given is removed, the board is reset to the minimal puzzle
+    pub fn minimize(&mut self) -> Vec<SquareValue> {
+        let mut removed = Vec::new();
+        let mut givens = self.givens();
+        let mut puzzle = self.with_givens(&givens);
+        if puzzle.count_solutions(2) != 1 {
+            return removed;
+        }
+        for pos in self.givens() {
+            let others: Vec<usize> = givens.iter().copied().filter(|given| *given != pos).collect();
+            let candidate = self.with_givens(&others);
+            if candidate.count_solutions(2) == 1 {
+                removed.push(puzzle.values[pos]);
+                puzzle = candidate;
+                givens = others;
+            }
+        }
+        if !removed.is_empty() {
+            self.values = puzzle.values;
+            self.initial = puzzle.initial;
+        }
+        removed
+    }
+
+    // A new board where square pos gets the possible values of square sources[pos], with every value v replaced
+    // by values[v - 1]. The extra constraints move along. Returns an error if one of them can't be moved
+    pub(crate) fn rearranged(&self, sources: &[usize], values: &[usize]) -> Result<Board, String> {
+        let mut result = Board::with_layout(&self.name, "", (*self.layout).clone());
+        let mut positions = vec![0; sources.len()];
+        for (pos, source) in sources.iter().enumerate() {
+            positions[*source] = pos;
+        }
+        for constraint in self.extra_constraints().iter() {
+            match constraint.rearranged(&positions, values) {
+                Some(moved) => result.add_constraint(moved),
+                None => return Err("The constraints of the board can't be transformed".to_string()),
+            }
+        }
+        let initial = sources.iter().map(|source| self.initial[*source]);
+        result.initial = Rc::new(initial.map(|value| if value == 0 { 0 } else { values[value - 1] }).collect());
+        for (pos, source) in sources.iter().enumerate() {
+            for value in self.values[*source].all_values() {
+                if !self.values[*source].can_have_value(value) {
+                    result.values[pos].cant_have_value(values[value - 1]);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    // Positions of the squares with a given value
+    fn givens(&self) -> Vec<usize> {
+        (0..self.initial.len()).filter(|pos| self.initial[*pos] != 0).collect()
+    }
+
+    // The puzzle with only some of the given values: those squares get their value, all other values are possible
+    fn with_givens(&self, givens: &[usize]) -> Board {
+        let mut board = self.clone();
+        board.depth = self.depth;
+        let initial: Vec<usize> =
+            (0..self.initial.len()).map(|pos| if givens.contains(&pos) { self.initial[pos] } else { 0 }).collect();
+        for (pos, square) in board.values.iter_mut().enumerate() {
+            *square = SquareValue::with_size(self.size());
+            square.at(self.layout.row_of(pos), self.layout.col_of(pos));
+            if initial[pos] != 0 {
+                square.set_known_value(initial[pos]);
+            }
+        }
+        board.initial = Rc::new(initial);
+        board
+    }
+
+    // Only the extra constraints prune, the solving techniques already enforce the default ones
+    pub(crate) fn apply_constraints(&mut self) -> bool {
+        let mut pruned = false;
+        let constraints = self.extra_constraints().to_vec();
+        for constraint in constraints.iter() {
+            pruned |= constraint.prune(self);
+        }
+        pruned
+    }
+
     fn generate_experiments(&self) -> Vec<Experimental> {
         let mut experiments: Vec<Experimental> = Vec::new();
         let candidate = self.find_cell_to_guess();
         if let Some(square) = candidate {
-            let guess_position = Board::position_of(square.row, square.col);
+            let guess_position = self.layout.position_of(square.row, square.col);
             let branching = square.possibilities();
-            for v in Board::ALL_VALUES {
-                if square.can_have_value(v) {
-                    if self.logging {
-                        self.report(format!(
-                            ">>> Guess that square ({},{}) has value {} at level {}",
-                            square.row, square.col, v, self.depth
-                        ));
-                    }
-                    let mut experimental = self.clone();
-                    experimental.values[guess_position].set_known_value(v);
-                    experiments.push(Experimental::new(experimental, square, v, branching));
+            let mut candidates = square.candidates();
+            if let Some(random) = &self.random {
+                random.borrow_mut().shuffle(&mut candidates);
+            }
+            for (rank, v) in candidates.into_iter().enumerate() {
+                let mut experimental = self.clone();
+                experimental.values[guess_position].set_known_value(v);
+                let mut experiment = Experimental::new(experimental, square, v, branching);
+                if self.random.is_some() {
+                    experiment.rank = rank + 1;
                 }
+                experiments.push(experiment);
             }
         }
         experiments
@@ -305,7 +605,7 @@ impl Board {
     // This is enough to solve really simple Sudokus
     // Example: [ 1 2 3 4 ] [ 4 ] [ 1 2 3 4 ] => [ 1 2 3 ] [ 4 ] [ 1 2 3 ]
 
-    fn propagate_all_known_values(&mut self) -> bool {
+    pub(crate) fn propagate_all_known_values(&mut self) -> bool {
         let mut progress_made = false;
         while self.propagate_known_values() {
             progress_made = true;
@@ -317,41 +617,24 @@ impl Board {
     fn propagate_known_values(&mut self) -> bool {
         let square = self.find_cell_to_propagate();
         if let Some(value) = square {
-            if self.logging {
-                self.report(format!(
-                    "Propagating value {} of ({},{})",
-                    value.value(),
-                    value.row,
-                    value.col
-                ));
-            }
-            self.propagate_known_values_in_all_except(
-                Board::all_values_in_column(value.col),
-                &value,
-            );
-            self.propagate_known_values_in_all_except(Board::all_values_in_row(value.row), &value);
-            self.propagate_known_values_in_all_except(
-                Board::all_values_in_subgrid(value.row_grid(), value.col_grid()),
-                &value,
-            );
-
-            self.values[Board::position_of(value.row, value.col)].has_been_propagated();
+            self.notify(SolveEvent::ValuePropagated {
+                row: value.row,
+                col: value.col,
+                value: value.value(),
+            });
+            let position = self.layout.position_of(value.row, value.col);
+            self.propagate_known_value_to_peers(position, value.value());
+
+            self.values[position].has_been_propagated();
             return true;
         }
         false
     }
 
-    fn propagate_known_values_in_all_except(
-        &mut self,
-        positions: [usize; 9],
-        except_square: &SquareValue,
-    ) {
-        let known_position = Board::position_of(except_square.row, except_square.col);
-        let known_value = except_square.value();
-        for pos in positions.iter() {
-            if *pos != known_position {
-                self.values[*pos].cant_have_value(known_value);
-            }
+    // The peers of a square are all other squares in the same row, column and subgrid, plus any extra peers of the layout
+    fn propagate_known_value_to_peers(&mut self, position: usize, known_value: usize) {
+        for pos in self.layout.peers(position).iter() {
+            self.values[*pos].cant_have_value(known_value);
         }
     }
 
@@ -367,33 +650,22 @@ impl Board {
     // We can conclude from this that the square must have that "singleton" value, as all values must be used
     // Example: [ 1 2 ] [ 1 2 3 ] [ 1 2 ] => [ 1 2 ] [ 3 ] [ 1 2 ]
 
-    fn promote_singletons(&mut self) -> bool {
+    pub(crate) fn promote_singletons(&mut self) -> bool {
         let mut promoted = false;
 
-        for row in Board::ALL_ROWS {
-            promoted |= self.promote_singleton_in(Board::all_values_in_row(row));
-        }
-
-        for col in Board::ALL_COLUMNS {
-            promoted |= self.promote_singleton_in(Board::all_values_in_column(col));
-        }
-
-        for rowgrid in 0..=2 {
-            for colgrid in 0..=2 {
-                promoted |=
-                    self.promote_singleton_in(Board::all_values_in_subgrid(rowgrid, colgrid));
-            }
+        for unit in 0..self.layout.units().len() {
+            promoted |= self.promote_singleton_in(unit);
         }
 
         promoted
     }
 
-    fn promote_singleton_in(&mut self, positions: [usize; 9]) -> bool {
+    fn promote_singleton_in(&mut self, unit: usize) -> bool {
         let mut promoted = false;
-        for value in Board::ALL_VALUES {
+        for value in 1..=self.size() {
             let mut occurences = 0;
             let mut foundpos = 0;
-            for pos in positions.iter() {
+            for pos in self.layout.units()[unit].iter() {
                 if self.values[*pos].can_have_value(value) {
                     occurences += 1;
                     foundpos = *pos;
@@ -401,6 +673,11 @@ impl Board {
             }
             if occurences == 1 && !self.values[foundpos].has_known_value() {
                 self.values[foundpos].set_known_value(value);
+                self.notify(SolveEvent::SingletonPromoted {
+                    row: self.layout.row_of(foundpos),
+                    col: self.layout.col_of(foundpos),
+                    value,
+                });
                 promoted = true;
             }
         }
@@ -408,65 +685,59 @@ impl Board {
         promoted
     }
 
-    fn find_naked_pairs(&mut self, pairs: &mut Vec<NakedPair>) -> bool {
+    pub(crate) fn find_naked_pairs(&mut self, pairs: &mut Vec<NakedPair>) -> bool {
         let mut found = false;
 
         let before = pairs.len();
-        for row in Board::ALL_ROWS {
-            found |= self.find_naked_pairs_in(Board::all_values_in_row(row), pairs);
-        }
-
-        for col in Board::ALL_COLUMNS {
-            found |= self.find_naked_pairs_in(Board::all_values_in_column(col), pairs);
-        }
-
-        for rowgrid in 0..=2 {
-            for colgrid in 0..=2 {
-                found |=
-                    self.find_naked_pairs_in(Board::all_values_in_subgrid(rowgrid, colgrid), pairs);
-            }
+        for unit in self.layout.units().iter() {
+            found |= self.find_naked_pairs_in(unit, pairs);
         }
 
         let after = pairs.len();
 
         if found {
-            for i in before..after {
-                let pair = pairs[i];
-                self.report(format!(
-                    "Cleaning naked pair {} and {} at ({},{}) and ({},{}) pos: {} {}",
-                    pair.value1, pair.value2,
-                    Board::row_of(pair.position1), Board::col_of(pair.position1),
-                    Board::row_of(pair.position2), Board::col_of(pair.position2),
-                    self.values[pair.position1].possibilities(),
-                    self.values[pair.position2].possibilities()
-                ));
-                self.clean_naked_pair(pair);
-                self.clean_neighbours_of_naked_pair(pair);
+            for pair in pairs[before..after].iter() {
+                self.notify(SolveEvent::NakedPairFound {
+                    first: (self.layout.row_of(pair.position1), self.layout.col_of(pair.position1)),
+                    second: (self.layout.row_of(pair.position2), self.layout.col_of(pair.position2)),
+                    value1: pair.value1,
+                    value2: pair.value2,
+                });
+                self.clean_naked_pair(*pair);
+                self.clean_neighbours_of_naked_pair(*pair);
             }
         }
 
-        return found;
+        found
     }
 
-    fn find_naked_pairs_in(&mut self, positions: [usize; 9], pairs: &mut Vec<NakedPair>) -> bool {
+    fn find_naked_pairs_in(&self, positions: &[usize], pairs: &mut Vec<NakedPair>) -> bool {
         let mut found = false;
-        for first_value in 1..9 {
-            for second_value in first_value + 1..=9 {
-                let mut matches: Vec<usize> = Vec::new();
-                let mut single = false;
-                let mut pure = true ;
-                for pos in positions.iter() {
-                    if self.values[*pos].can_have_value(first_value) && self.values[*pos].can_have_value(second_value) {
-                        matches.push(*pos);
-                        pure &= self.values[*pos].possibilities() == 2 ;
-                    } else if self.values[*pos].can_have_value(first_value) || self.values[*pos].can_have_value(second_value) {
-                        single = true;
-                    }
+        let size = self.size();
+        // The squares of the unit as bits, one bit per index in positions: the squares that can have each value,
+        // and the squares with only two possible values
+        let mut squares_with = [0u32; SquareValue::MAX_VALUES + 1];
+        let mut two_possibilities = 0u32;
+        for (index, pos) in positions.iter().enumerate() {
+            let square = self.values[*pos];
+            for (value, squares) in squares_with.iter_mut().enumerate().take(size + 1).skip(1) {
+                if square.can_have_value(value) {
+                    *squares |= 1 << index;
                 }
-                if matches.len() == 2 && (!single || pure) {
+            }
+            if square.possibilities() == 2 {
+                two_possibilities |= 1 << index;
+            }
+        }
+        for first_value in 1..size {
+            for second_value in first_value + 1..=size {
+                let both = squares_with[first_value] & squares_with[second_value];
+                let single = both != squares_with[first_value] | squares_with[second_value];
+                let pure = both & !two_possibilities == 0;
+                if both.count_ones() == 2 && (!single || pure) {
                     let pair = NakedPair {
-                        position1: matches[0],
-                        position2: matches[1],
+                        position1: positions[both.trailing_zeros() as usize],
+                        position2: positions[(both & (both - 1)).trailing_zeros() as usize],
                         value1: first_value,
                         value2: second_value,
                     };
@@ -481,7 +752,7 @@ impl Board {
         found
     }
     fn clean_naked_pair(&mut self, pair: NakedPair) {
-        for v in Board::ALL_VALUES {
+        for v in 1..=self.size() {
             if v != pair.value1 && v != pair.value2 {
                 self.values[pair.position1].cant_have_value(v);
                 self.values[pair.position2].cant_have_value(v);
@@ -489,20 +760,17 @@ impl Board {
         }
     }
 
+    // The pair's values can be removed from all other squares in every unit that contains both squares of the pair
     fn clean_neighbours_of_naked_pair(&mut self, pair: NakedPair) {
-        if Board::row_of(pair.position1) == Board::row_of(pair.position2) {
-            self.clean_neighbours(pair, Board::all_values_in_row(Board::row_of(pair.position1)));
-        }
-        if Board::col_of(pair.position1) == Board::col_of(pair.position2) {
-            self.clean_neighbours(pair, Board::all_values_in_column(Board::col_of(pair.position1)));
-        }
-        if Board::colgrid_of(pair.position1) == Board::colgrid_of(pair.position2) &&
-            Board::rowgrid_of(pair.position1) == Board::rowgrid_of(pair.position2) {
-            self.clean_neighbours(pair, Board::all_values_in_subgrid(Board::rowgrid_of(pair.position1), Board::colgrid_of(pair.position1)));
+        let layout = self.layout.clone();
+        for unit in layout.units_of(pair.position1).iter() {
+            if layout.units_of(pair.position2).contains(unit) {
+                self.clean_neighbours(pair, &layout.units()[*unit]);
+            }
         }
     }
 
-    fn clean_neighbours(&mut self, pair: NakedPair, positions: [usize; 9]) {
+    fn clean_neighbours(&mut self, pair: NakedPair, positions: &[usize]) {
         for pos in positions.iter() {
             if *pos != pair.position1 && *pos != pair.position2 {
                 self.values[*pos].cant_have_value(pair.value1);
@@ -517,13 +785,13 @@ impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut output = String::new();
         output += "\n";
-        for row in Board::ALL_ROWS {
-            for col in Board::ALL_COLUMNS {
-                let value = &self.values[Board::position_of(row, col)];
+        for row in 1..=self.size() {
+            for col in 1..=self.size() {
+                let value = self.square(row, col);
                 if !value.has_known_value() {
                     output.push('.');
                 } else {
-                    output.push_str(&value.value().to_string());
+                    output.push(symbol_of(value.value()));
                 }
             }
             output += "\n";
@@ -545,22 +813,46 @@ impl fmt::Display for Game {
 // _____6___ = value is known to be 6
 // XXXXXXXXX = inconsistent state, nothing is possible
 // An inconsistent square (without possibilities) is shown as 'XXXXXXXXX'
+// In a jigsaw sudoku, every square is separated and prefixed with the letter of its region: b:..3..67..
+// In a Windoku, squares in the extra windows are prefixed with '#', other squares with a space
 impl fmt::Debug for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = self.size();
+        let box_rows = self.layout.box_rows();
+        let box_cols = self.layout.box_cols();
+        // Each square takes size + 1 characters, plus one for the Windoku marker
+        let square_width = if self.layout.has_windows() { size + 2 } else { size + 1 };
+        let width = size * square_width + 7;
+        let title = format!(" Level {} ", self.depth);
+        let left = (width - 1 - title.len()) / 2;
         let mut output = String::new();
-        output.push_str(&format!("\n  ------------------------------------------- Level {} --------------------------------------------\n", self.depth));
-        for row in Board::ALL_ROWS {
+        output.push_str(&format!(
+            "\n  {}{}{}\n",
+            "-".repeat(left),
+            title,
+            "-".repeat(width - 1 - left - title.len())
+        ));
+        for row in 1..=size {
             output.push_str(" | ");
-            for col in Board::ALL_COLUMNS {
-                let square = self.values[Board::position_of(row, col)];
+            for col in 1..=size {
+                let square = self.square(row, col);
+                if self.layout.is_jigsaw() {
+                    let region = self.layout.region_of(self.layout.position_of(row, col));
+                    output.push((b'a' + region as u8) as char);
+                    output.push(':');
+                }
+                if self.layout.has_windows() {
+                    let in_window = self.layout.in_window(self.layout.position_of(row, col));
+                    output.push(if in_window { '#' } else { ' ' });
+                }
                 output.push_str(&format!("{:?} ", square));
-                if col % 3 == 0 {
+                if col % box_cols == 0 {
                     output.push_str(" | ");
                 }
             }
             output.push('\n');
-            if row % 3 == 0 {
-                output.push_str(" -------------------------------------------------------------------------------------------------\n");
+            if row % box_rows == 0 {
+                output.push_str(&format!(" {}\n", "-".repeat(width)));
             }
         }
 
@@ -574,31 +866,57 @@ impl fmt::Debug for Game {
     }
 }
 
-// Parses a multi-line string with the starting values of the Sudoku
-// Digit => value of the digit 1..9
-// '.'   => 0
+// Parses a multi-line string with the starting values of a Sudoku of the given size
+// Symbol => value of the symbol: digits 1..9, letters A..P for 10..25
+// '.'    => 0
 // values are arranged row per row
-// Empty (less than 9 chars) are skipped
-// The input doesn't have to contain 9 rows. If rows are missing, they are assumed to be empty (= 0 values)
-fn parse_initial_sudoku_values(values: &str) -> [usize; 81] {
+// A line with exactly size whitespace-separated tokens is read token by token, so values above 9 can also be
+// written as numbers: "10 . 3 16 ..." Tokens "." and "0" are empty
+// Otherwise, the first size characters of the line are read. Shorter lines are skipped
+// The input doesn't have to contain all rows. If rows are missing, they are assumed to be empty (= 0 values)
+fn parse_initial_sudoku_values(values: &str, size: usize) -> Vec<usize> {
     let lines = values.lines();
-    let mut result: [usize; 81] = [0; 81];
+    let mut result: Vec<usize> = vec![0; size * size];
     let mut row = 0;
     for line in lines {
-        if line.len() >= 9 {
-            row += 1;
-            for col in Board::ALL_COLUMNS {
-                let kar = line.chars().nth(col - 1).unwrap();
-                if ('1'..='9').contains(&kar) {
-                    result[Board::position_of(row, col)] = kar.to_digit(10).unwrap() as usize;
-                }
+        if row == size {
+            break;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let row_values: Vec<usize> = if tokens.len() == size && size > 1 {
+            tokens.iter().map(|token| parse_token(token)).collect()
+        } else if line.chars().count() >= size {
+            line.chars()
+                .take(size)
+                .map(|kar| value_of(kar).unwrap_or(0))
+                .collect()
+        } else {
+            continue;
+        };
+        for (col, value) in row_values.iter().enumerate() {
+            if *value <= size {
+                result[row * size + col] = *value;
             }
         }
+        row += 1;
     }
 
     result
 }
 
+fn parse_token(token: &str) -> usize {
+    match token.parse::<usize>() {
+        Ok(number) => number,
+        Err(_) => {
+            let mut symbols = token.chars();
+            match (symbols.next(), symbols.next()) {
+                (Some(symbol), None) => value_of(symbol).unwrap_or(0),
+                _ => 0,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -612,7 +930,7 @@ mod tests {
 
     #[test]
     fn test_parse_initial_values() {
-        let output = parse_initial_sudoku_values(easy_sudoku());
+        let output = parse_initial_sudoku_values(easy_sudoku(), 9);
         assert_eq!(81, output.len());
 
         // First line
@@ -670,13 +988,51 @@ mod tests {
 
     #[test]
     fn test_parse_empty_initial_values() {
-        let output = parse_initial_sudoku_values("");
+        let output = parse_initial_sudoku_values("", 9);
         assert_eq!(81, output.len());
         for pos in 0..81 {
             assert_eq!(0, output[pos]);
         }
     }
 
+    #[test]
+    fn test_parse_letters_for_values_above_9() {
+        let output = parse_initial_sudoku_values("\n1A.G............\nb...............\n", 16);
+        assert_eq!(256, output.len());
+        assert_eq!(vec![1, 10, 0, 16], output[0..4].to_vec());
+        assert_eq!(0, output[15]);
+        assert_eq!(11, output[16]);
+    }
+
+    #[test]
+    fn test_parse_tokens_for_values_above_9() {
+        let output = parse_initial_sudoku_values("\n10 . 3 0 12 1\n  .  .  .  .  .  6\n", 6);
+        // 10 and 12 are too large for a 6x6 grid
+        assert_eq!(vec![0, 0, 3, 0, 0, 1], output[0..6].to_vec());
+        assert_eq!(vec![0, 0, 0, 0, 0, 6], output[6..12].to_vec());
+
+        let output = parse_initial_sudoku_values("10 . 3 0 12 1 . . . . . 16", 12);
+        assert_eq!(vec![10, 0, 3, 0, 12, 1, 0, 0, 0, 0, 0, 0], output[0..12].to_vec());
+    }
+
+    #[test]
+    fn test_small_board_prints_initial_values() {
+        let board = Board::with_layout("kids", kids_6x6(), Layout::new(2, 3));
+        assert_eq!(6, board.size());
+        assert_eq!(kids_6x6(), board.to_string());
+    }
+
+    #[test]
+    fn test_debug_output_marks_windows() {
+        let mut layout = Layout::new(2, 2);
+        layout.add_windows();
+        let board = Board::with_layout("windoku", "12..\n....\n....\n....\n", layout);
+        let debug = format!("{:?}", board);
+        let rows: Vec<&str> = debug.lines().filter(|line| line.starts_with(" | ")).collect();
+        assert_eq!(" |  1___  _2__  |  1234  1234  | ", rows[0]);
+        assert_eq!(" |  1234 #1234  | #1234  1234  | ", rows[1]);
+    }
+
     #[test]
     fn test_game_prints_initial_values() {
         let game = Board::new("easy", easy_sudoku());
@@ -688,7 +1044,7 @@ mod tests {
         let mut game = Board::new("easy", easy_sudoku());
         game.solve();
         for col in 1..9 {
-            let cell = game.values[Board::position_of(1, col)];
+            let cell = game.square(1, col);
             if !cell.has_known_value() {
                 assert_eq!(9 - 4, cell.possibilities()); // 4 cells are known
             }
@@ -697,4 +1053,110 @@ mod tests {
         assert_eq!(81, game.possibilities());
         assert_eq!(easy_sudoku_solution(), game.to_string());
     }
+
+    #[test]
+    fn test_repeated_values_break_the_rules() {
+        let board = Board::new("repeated", "1.......1");
+        assert!(board.contains_contradiction());
+        assert!(!Board::new("complete", easy_sudoku_solution().replacen('6', "9", 1).as_str()).solved());
+        assert!(!Board::new("easy", easy_sudoku()).contains_contradiction());
+
+        // The default constraints follow the extra peers of the layout too
+        let mut layout = Layout::classic();
+        layout.add_anti_king();
+        assert!(Board::with_layout("anti-king", "1........\n.1.......", layout.clone()).contains_contradiction());
+        let apart = Board::with_layout("anti-king", "1........\n.........\n.........\n.1.......", layout);
+        assert!(!apart.contains_contradiction());
+    }
+
+    #[test]
+    fn test_minimize_removes_redundant_givens() {
+        let mut game = Game::new("easy", easy_sudoku());
+        assert!(!game.is_minimal());
+        let givens = game.board.givens().len();
+        let removed = game.minimize();
+        assert!(!removed.is_empty());
+        assert!(game.is_minimal());
+        assert_eq!(givens - removed.len(), game.board.givens().len());
+        for square in removed.iter() {
+            assert!(square.has_known_value());
+            assert!(!game.board.square(square.row, square.col).has_known_value());
+        }
+        assert!(game.solve());
+        assert_eq!(easy_sudoku_solution(), game.to_string());
+    }
+
+    #[test]
+    fn test_minimize_after_solving_works_on_the_givens() {
+        let mut game = Game::new("easy", easy_sudoku());
+        assert!(game.solve());
+        assert!(!game.is_minimal());
+        let givens = game.board.givens().len();
+        let removed = game.minimize();
+        assert!(!removed.is_empty());
+        assert_eq!(givens - removed.len(), game.board.givens().len());
+        assert!(game.is_minimal());
+        // The board is the minimal puzzle again, not the solution
+        assert!(!game.solved());
+        let known = (0..81).filter(|pos| game.board.square_at(*pos).has_known_value()).count();
+        assert_eq!(game.board.givens().len(), known);
+        assert!(game.solve());
+        assert_eq!(easy_sudoku_solution(), game.to_string());
+    }
+
+    #[test]
+    fn test_puzzle_without_single_solution_is_not_minimal() {
+        let mut game = Game::new("empty", "");
+        assert!(!game.is_minimal());
+        assert!(game.minimize().is_empty());
+    }
+
+    #[test]
+    fn test_random_solved_boards() {
+        let board = Board::random_solved(1).unwrap();
+        assert!(board.solved());
+        assert_eq!(0, board.depth);
+        assert_eq!(board.to_string(), Board::random_solved(1).unwrap().to_string());
+        assert_ne!(board.to_string(), Board::random_solved(2).unwrap().to_string());
+        assert!(Board::random_solved_with_layout(3, Layout::new(2, 3)).unwrap().solved());
+
+        let mut layout = Layout::new(2, 2);
+        layout.add_anti_king();
+        assert!(Board::random_solved_with_layout(1, layout).is_none());
+    }
+
+    #[test]
+    fn test_digits_of_random_solved_boards_are_uniform() {
+        // 6x6 boards keep the test fast. counts[pos][value - 1]: how often a square got the value
+        let boards = 120;
+        let mut counts = vec![[0usize; 6]; 36];
+        for seed in 0..boards {
+            let board = Board::random_solved_with_layout(seed as u64, Layout::new(2, 3)).unwrap();
+            for (pos, count) in counts.iter_mut().enumerate() {
+                count[board.square_at(pos).value() - 1] += 1;
+            }
+        }
+        // Pearson's chi-squared statistic, summed over all squares: 36 * 5 degrees of freedom, so about 180 if uniform
+        let expected = boards as f64 / 6.0;
+        let chi_squared: f64 = counts
+            .iter()
+            .flat_map(|count| count.iter())
+            .map(|count| (*count as f64 - expected).powi(2) / expected)
+            .sum();
+        assert!(chi_squared < 250.0);
+        assert!(counts.iter().flat_map(|count| count.iter()).all(|count| *count >= 8));
+    }
+
+    #[test]
+    fn test_randomized_game_finds_different_solutions() {
+        let solutions: Vec<String> = (0..4)
+            .map(|seed| {
+                let mut game = Game::with_layout("empty", "", Layout::new(2, 2));
+                game.randomize(seed);
+                assert!(game.solve());
+                game.to_string()
+            })
+            .collect();
+        assert!(solutions.iter().any(|solution| *solution != solutions[0]));
+    }
 }
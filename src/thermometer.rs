@@ -0,0 +1,133 @@
+// Thermo Sudoku: the values on a thermometer strictly increase, starting from the bulb
+// A thermometer is a path of touching squares (horizontally, vertically or diagonally)
+//
+// The possible values of every square are limited to a range:
+// - Going up from the bulb, a square must be higher than the lowest possible value of the square before it
+// - Going down from the top, a square must be lower than the highest possible value of the square after it
+// Example: on a 9x9 grid, a thermometer of 4 squares starts with 1..6 and ends with 4..9
+
+use crate::constraint::Constraint;
+use crate::layout::Layout;
+use crate::sudoku::Board;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Thermometer {
+    squares: Vec<usize>,
+}
+
+impl Thermometer {
+    // The squares of the thermometer, starting with the bulb
+    pub fn new(squares: Vec<usize>) -> Self {
+        Self { squares }
+    }
+
+    // Parses thermometers, one per line, as a list of squares starting with the bulb. E.g.
+    // r1c1 r1c2 r1c3
+    // r4c4 r5c5
+    // Empty lines are skipped
+    pub fn parse(thermometers: &str, layout: &Layout) -> Result<Vec<Self>, String> {
+        let mut result = Vec::new();
+        for line in thermometers.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let squares = layout.parse_squares(line)?;
+            if squares.len() < 2 || squares.len() > layout.size() {
+                return Err(format!("Thermometer '{}' needs 2 to {} squares", line, layout.size()));
+            }
+            if let Some((pos, other)) = layout.gap_in(&squares) {
+                return Err(format!(
+                    "Thermometer '{}' has a gap between {} and {}",
+                    line,
+                    layout.name_of(pos),
+                    layout.name_of(other)
+                ));
+            }
+            result.push(Thermometer::new(squares));
+        }
+        Ok(result)
+    }
+
+    pub fn squares(&self) -> &[usize] {
+        &self.squares
+    }
+}
+
+impl Constraint for Thermometer {
+    fn prune(&self, board: &mut Board) -> bool {
+        let size = board.size();
+        let mut pruned = false;
+        let mut lowest = 0;
+        for pos in self.squares.iter() {
+            pruned |= board.eliminate_outside(*pos, lowest + 1, size);
+            lowest = board.square_at(*pos).value();
+        }
+        let mut highest = size + 1;
+        for pos in self.squares.iter().rev() {
+            pruned |= board.eliminate_outside(*pos, 1, highest.saturating_sub(1));
+            highest = board.square_at(*pos).highest_value();
+        }
+        pruned
+    }
+
+    fn is_satisfied(&self, board: &Board) -> bool {
+        self.squares
+            .windows(2)
+            .all(|pair| board.square_at(pair[0]).value() < board.square_at(pair[1]).value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_thermometers() {
+        let layout = Layout::classic();
+        let thermometers = Thermometer::parse("\nr1c1 r1c2 r2c3\n\nr9c9 r8c9\n", &layout).unwrap();
+        assert_eq!(2, thermometers.len());
+        assert_eq!(&[0, 1, 11], thermometers[0].squares());
+        assert_eq!(&[80, 71], thermometers[1].squares());
+    }
+
+    #[test]
+    fn test_invalid_thermometers_are_rejected() {
+        let layout = Layout::new(2, 2);
+        assert_eq!(
+            Err("Thermometer 'r1c1' needs 2 to 4 squares".to_string()),
+            Thermometer::parse("r1c1", &layout)
+        );
+        assert_eq!(
+            Err("Thermometer 'r1c1 r1c2 r1c3 r1c4 r2c4' needs 2 to 4 squares".to_string()),
+            Thermometer::parse("r1c1 r1c2 r1c3 r1c4 r2c4", &layout)
+        );
+        assert_eq!(
+            Err("Thermometer 'r1c1 r1c3' has a gap between r1c1 and r1c3".to_string()),
+            Thermometer::parse("r1c1 r1c3", &layout)
+        );
+        assert_eq!(Err("Invalid square 'x'".to_string()), Thermometer::parse("r1c1 x", &layout));
+    }
+
+    #[test]
+    fn test_thermometer_limits_ranges_from_both_ends() {
+        let thermometer = Thermometer::new(vec![0, 1, 2, 3]);
+        let mut board = Board::new("thermo", "");
+        assert!(thermometer.prune(&mut board));
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], board.square_at(0).candidates());
+        assert_eq!(vec![2, 3, 4, 5, 6, 7], board.square_at(1).candidates());
+        assert_eq!(vec![4, 5, 6, 7, 8, 9], board.square_at(3).candidates());
+        assert!(!thermometer.prune(&mut board));
+
+        // A known value in the middle pushes the bounds of the other squares
+        let mut board = Board::new("thermo", ".5.......");
+        thermometer.prune(&mut board);
+        assert_eq!(vec![1, 2, 3, 4], board.square_at(0).candidates());
+        assert_eq!(vec![6, 7, 8], board.square_at(2).candidates());
+        assert_eq!(vec![7, 8, 9], board.square_at(3).candidates());
+    }
+
+    #[test]
+    fn test_is_satisfied_requires_increasing_values() {
+        let thermometer = Thermometer::new(vec![0, 1, 2]);
+        assert!(thermometer.is_satisfied(&Board::new("ok", "137......")));
+        assert!(!thermometer.is_satisfied(&Board::new("equal", "133......")));
+        assert!(!thermometer.is_satisfied(&Board::new("decreasing", "731......")));
+    }
+}
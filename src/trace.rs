@@ -0,0 +1,166 @@
+// Observer that writes a trace of a full solve as JSON Lines: one JSON object per solving step
+// Each line contains the sequence number of the step, the depth of the board, the event and the candidate grid after the step
+// The candidate grid is a list of rows, each row a list of squares, each square the list of values still possible
+// E.g. {"step":3,"depth":0,"event":{"event":"ValuePropagated","row":1,"col":4,"value":2},"candidates":[[[3,4,5],[3],...]]}
+//
+// Only available with the "serde" feature: cargo build --features serde
+
+use crate::observer::{Observer, SolveEvent};
+use crate::sudoku::Board;
+use serde::Serialize;
+use std::io;
+use std::io::Write;
+
+#[derive(Serialize)]
+struct TraceLine<'a> {
+    step: usize,
+    depth: usize,
+    event: &'a SolveEvent,
+    candidates: Vec<Vec<Vec<usize>>>,
+}
+
+// The trace is written to any Write implementation: a file, stdout or a Vec<u8> in memory
+// Observers can't return errors, so the first write error is kept and no more lines are written after it
+pub struct JsonTrace<W: Write> {
+    writer: W,
+    steps: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonTrace<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            steps: 0,
+            error: None,
+        }
+    }
+
+    // Number of lines written so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // Returns the first error that occurred while writing the trace, if any
+    pub fn result(&self) -> Result<(), &io::Error> {
+        match &self.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    fn candidates(board: &Board) -> Vec<Vec<Vec<usize>>> {
+        (1..=board.size())
+            .map(|row| {
+                (1..=board.size())
+                    .map(|col| board.square(row, col).candidates())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn write_line(&mut self, board: &Board, event: &SolveEvent) -> io::Result<()> {
+        let line = TraceLine {
+            step: self.steps,
+            depth: board.depth,
+            event,
+            candidates: JsonTrace::<W>::candidates(board),
+        };
+        serde_json::to_writer(&mut self.writer, &line)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+impl<W: Write> Observer for JsonTrace<W> {
+    fn notify(&mut self, board: &Board, event: &SolveEvent) {
+        if self.error.is_none() {
+            match self.write_line(board, event) {
+                Ok(()) => self.steps += 1,
+                Err(error) => self.error = Some(error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::*;
+    use crate::sudoku::Game;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn trace_of(name: &str, initial: &str) -> Vec<serde_json::Value> {
+        let trace = Rc::new(RefCell::new(JsonTrace::new(Vec::new())));
+        let mut game = Game::new(name, initial);
+        game.observer(trace.clone());
+        game.solve();
+
+        let trace = trace.borrow();
+        assert!(trace.result().is_ok());
+        let output = String::from_utf8(trace.writer().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(trace.steps(), lines.len());
+        lines
+    }
+
+    #[test]
+    fn test_trace_contains_one_line_per_step() {
+        let lines = trace_of("easy", easy_sudoku());
+
+        assert_eq!("Solving", lines[0]["event"]["event"]);
+        assert_eq!(0, lines[0]["step"]);
+        assert_eq!(0, lines[0]["depth"]);
+        // First row, fourth column is given as 2
+        assert_eq!(serde_json::json!([2]), lines[0]["candidates"][0][3]);
+        assert_eq!(9, lines[0]["candidates"][0][0].as_array().unwrap().len());
+
+        let last = lines.last().unwrap();
+        assert_eq!("Solved", last["event"]["event"]);
+        assert_eq!(serde_json::json!([4]), last["candidates"][0][0]);
+    }
+
+    #[test]
+    fn test_trace_records_guesses_and_backtracks() {
+        let lines = trace_of("not fun", not_fun_sudoku1());
+
+        let events: Vec<&str> = lines
+            .iter()
+            .map(|line| line["event"]["event"].as_str().unwrap())
+            .collect();
+        assert!(events.contains(&"GuessMade"));
+        assert!(events.contains(&"Backtracked"));
+        assert!(lines.iter().any(|line| line["depth"].as_u64().unwrap() > 0));
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace_keeps_first_write_error() {
+        let trace = Rc::new(RefCell::new(JsonTrace::new(FailingWriter)));
+        let mut game = Game::new("easy", easy_sudoku());
+        game.observer(trace.clone());
+        game.solve();
+
+        assert!(game.solved());
+        assert_eq!(0, trace.borrow().steps());
+        assert!(trace.borrow().result().is_err());
+    }
+}
@@ -0,0 +1,428 @@
+// Transformations that turn a valid grid into another valid grid, so a puzzle can be shown in a different form
+// with the same difficulty and a single solution, if the original had one:
+// - Relabel the values, e.g. swap all 1s and 2s
+// - Reorder the bands (rows of boxes) or the stacks (columns of boxes)
+// - Reorder the rows within a band, or the columns within a stack
+// - Transpose (mirror over the main diagonal) or rotate a quarter turn clockwise. Only for square boxes
+//
+// Bands, stacks, rows within a band and columns within a stack are numbered from 0. An order lists, for each new
+// place, the old place that moves there: order [2, 0, 1] moves band 2 to the top
+//
+// Transformations apply to the given values and to the possible values of the other squares. They need a layout
+// with only rows, columns and regular boxes. Extra constraints of the board move along if they support it
+// (see Constraint::rearranged), e.g. Distinct. Other constraints, like a thermometer, can't be transformed
+//
+// Puzzles that can be transformed into each other are equivalent. The canonical form of a puzzle is the smallest
+// of all equivalent puzzles, comparing the values row by row, with empty squares after all values. Equivalent
+// puzzles have the same canonical form. It is found row by row:
+// - Start with every order of the columns (of the grid and of its transpose), without any rows
+// - Add every row that the bands allow. Relabel the values in order of appearance: the first value becomes 1, etc.
+// - Keep only the candidates with the smallest rows so far, and continue with the next row
+// - Candidates whose remaining rows look the same end up with the same rows, so only one of them is kept. Otherwise
+//   sparse grids keep far too many candidates: on an empty grid, every order of the columns and rows ties
+
+use crate::layout::Layout;
+use crate::square_value::symbol_of;
+use crate::sudoku::Board;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Transformation {
+    // Value v becomes values[v - 1]
+    Relabel(Vec<usize>),
+    PermuteBands(Vec<usize>),
+    PermuteStacks(Vec<usize>),
+    PermuteRows { band: usize, order: Vec<usize> },
+    PermuteColumns { stack: usize, order: Vec<usize> },
+    Transpose,
+    Rotate,
+}
+
+impl Transformation {
+    pub fn apply(&self, board: &Board) -> Result<Board, String> {
+        let layout = board.layout();
+        self.check(layout)?;
+        let values = match self {
+            Transformation::Relabel(values) => values.clone(),
+            _ => (1..=layout.size()).collect(),
+        };
+        let sources: Vec<usize> = (0..layout.cells())
+            .map(|pos| self.source_of(layout, layout.row_of(pos) - 1, layout.col_of(pos) - 1))
+            .collect();
+        board.rearranged(&sources, &values)
+    }
+
+    fn check(&self, layout: &Layout) -> Result<(), String> {
+        if !layout.is_plain() {
+            return Err("Only grids with rows, columns and regular boxes can be transformed".to_string());
+        }
+        let (size, box_rows, box_cols) = (layout.size(), layout.box_rows(), layout.box_cols());
+        match self {
+            Transformation::Relabel(values) => {
+                let places: Vec<usize> = values.iter().map(|value| value.wrapping_sub(1)).collect();
+                check_order(&places, size).map_err(|_| format!("Invalid relabelling {:?}", values))
+            }
+            Transformation::PermuteBands(order) => check_order(order, size / box_rows),
+            Transformation::PermuteStacks(order) => check_order(order, size / box_cols),
+            Transformation::PermuteRows { band, .. } if *band >= size / box_rows => {
+                Err(format!("Band {} is outside the grid", band))
+            }
+            Transformation::PermuteRows { order, .. } => check_order(order, box_rows),
+            Transformation::PermuteColumns { stack, .. } if *stack >= size / box_cols => {
+                Err(format!("Stack {} is outside the grid", stack))
+            }
+            Transformation::PermuteColumns { order, .. } => check_order(order, box_cols),
+            Transformation::Transpose | Transformation::Rotate if box_rows != box_cols => {
+                Err("Only grids with square boxes can be transposed or rotated".to_string())
+            }
+            Transformation::Transpose | Transformation::Rotate => Ok(()),
+        }
+    }
+
+    // Position of the square that moves to (row, col), 0-based
+    fn source_of(&self, layout: &Layout, row: usize, col: usize) -> usize {
+        let (size, box_rows, box_cols) = (layout.size(), layout.box_rows(), layout.box_cols());
+        let (row, col) = match self {
+            Transformation::Relabel(_) => (row, col),
+            Transformation::PermuteBands(order) => (order[row / box_rows] * box_rows + row % box_rows, col),
+            Transformation::PermuteStacks(order) => (row, order[col / box_cols] * box_cols + col % box_cols),
+            Transformation::PermuteRows { band, order } if row / box_rows == *band => {
+                (band * box_rows + order[row % box_rows], col)
+            }
+            Transformation::PermuteColumns { stack, order } if col / box_cols == *stack => {
+                (row, stack * box_cols + order[col % box_cols])
+            }
+            Transformation::PermuteRows { .. } | Transformation::PermuteColumns { .. } => (row, col),
+            Transformation::Transpose => (col, row),
+            Transformation::Rotate => (size - 1 - col, row),
+        };
+        row * size + col
+    }
+}
+
+// A canonical form that is being built, row by row
+#[derive(Clone)]
+struct Candidate {
+    // Index of the grid: the original or its transpose
+    grid: usize,
+    // The old row and column of every new row and column
+    rows: Vec<usize>,
+    columns: Vec<usize>,
+    // New label of every old value, 0 if the value hasn't appeared yet
+    labels: Vec<usize>,
+}
+
+impl Candidate {
+    // Rows that can come next: any row of an unused band when a new band starts, otherwise another row of the same band
+    fn next_rows(&self, layout: &Layout) -> Vec<usize> {
+        let (size, box_rows) = (layout.size(), layout.box_rows());
+        match self.rows.last() {
+            Some(last) if self.rows.len() % box_rows != 0 => {
+                let band = last / box_rows;
+                (band * box_rows..(band + 1) * box_rows).filter(|row| !self.rows.contains(row)).collect()
+            }
+            _ => (0..size).filter(|row| !self.rows.iter().any(|used| used / box_rows == row / box_rows)).collect(),
+        }
+    }
+
+    // Adds a row and returns its relabelled values, with size + 1 for an empty square
+    fn add_row(&mut self, values: &[usize], row: usize) -> Vec<usize> {
+        let size = self.columns.len();
+        self.rows.push(row);
+        let mut result = Vec::with_capacity(size);
+        for col in self.columns.iter() {
+            let value = values[row * size + col];
+            if value == 0 {
+                result.push(size + 1);
+                continue;
+            }
+            if self.labels[value] == 0 {
+                self.labels[value] = self.labels.iter().filter(|label| **label != 0).count() + 1;
+            }
+            result.push(self.labels[value]);
+        }
+        result
+    }
+
+    // The rows that haven't been added yet, with their values as this candidate shows them: the new label of a value
+    // that already appeared, size + 1 for an empty square, and size + 1 + the old value for any other value
+    // Canonical forms go up to 9x9 grids, so every number fits in a byte
+    fn remaining_rows(&self, values: &[usize]) -> Vec<u8> {
+        let size = self.columns.len();
+        let mut result = Vec::new();
+        for row in (0..size).filter(|row| !self.rows.contains(row)) {
+            result.push(row as u8);
+            for col in self.columns.iter() {
+                let value = values[row * size + col];
+                let shown = if value != 0 && self.labels[value] != 0 { self.labels[value] } else { size + 1 + value };
+                result.push(shown as u8);
+            }
+        }
+        result
+    }
+}
+
+// The canonical form of the given values of a board, one row per line, '.' for an empty square. See the top of this file
+pub fn canonical_form(board: &Board) -> Result<String, String> {
+    let layout = board.layout();
+    if !layout.is_plain() {
+        return Err("Only grids with rows, columns and regular boxes have a canonical form".to_string());
+    }
+    if layout.size() > 9 {
+        return Err("Canonical forms are only supported up to 9x9 grids".to_string());
+    }
+    let size = layout.size();
+    let values: Vec<usize> = (0..layout.cells())
+        .map(|pos| board.square_at(pos))
+        .map(|square| if square.has_known_value() { square.value() } else { 0 })
+        .collect();
+    let mut grids = vec![values.clone()];
+    if layout.box_rows() == layout.box_cols() {
+        grids.push((0..layout.cells()).map(|pos| values[(pos % size) * size + pos / size]).collect());
+    }
+    let mut candidates = Vec::new();
+    for grid in 0..grids.len() {
+        for columns in column_orders(layout) {
+            candidates.push(Candidate {
+                grid,
+                rows: Vec::new(),
+                columns,
+                labels: vec![0; size + 1],
+            });
+        }
+    }
+
+    let mut result = String::new();
+    for _ in 0..size {
+        let mut smallest: Option<Vec<usize>> = None;
+        let mut kept = Vec::new();
+        for candidate in candidates.iter() {
+            for row in candidate.next_rows(layout) {
+                let mut extended = candidate.clone();
+                let line = extended.add_row(&grids[candidate.grid], row);
+                match smallest.as_ref().map(|smallest| line.cmp(smallest)) {
+                    Some(Ordering::Greater) => {}
+                    Some(Ordering::Equal) => kept.push(extended),
+                    _ => {
+                        smallest = Some(line);
+                        kept = vec![extended];
+                    }
+                }
+            }
+        }
+        let smallest = smallest.unwrap_or_default();
+        result.extend(smallest.iter().map(|value| if *value > size { '.' } else { symbol_of(*value) }));
+        result.push('\n');
+        let mut remaining = HashSet::new();
+        candidates = kept
+            .into_iter()
+            .filter(|candidate| remaining.insert(candidate.remaining_rows(&grids[candidate.grid])))
+            .collect();
+    }
+    Ok(result)
+}
+
+// Two puzzles are equivalent if a combination of transformations turns one into the other
+pub fn is_equivalent(board: &Board, other: &Board) -> Result<bool, String> {
+    Ok(board.size() == other.size() && canonical_form(board)? == canonical_form(other)?)
+}
+
+// Every order of the columns that keeps the stacks together
+fn column_orders(layout: &Layout) -> Vec<Vec<usize>> {
+    let box_cols = layout.box_cols();
+    let mut result = Vec::new();
+    for stacks in permutations(layout.size() / box_cols) {
+        let mut orders: Vec<Vec<usize>> = vec![Vec::new()];
+        for stack in stacks {
+            orders = orders
+                .iter()
+                .flat_map(|order| {
+                    permutations(box_cols).into_iter().map(move |within| {
+                        let mut extended = order.clone();
+                        extended.extend(within.iter().map(|col| stack * box_cols + col));
+                        extended
+                    })
+                })
+                .collect();
+        }
+        result.append(&mut orders);
+    }
+    result
+}
+
+// All orders of 0..count
+fn permutations(count: usize) -> Vec<Vec<usize>> {
+    let mut result: Vec<Vec<usize>> = vec![Vec::new()];
+    for _ in 0..count {
+        result = result
+            .iter()
+            .flat_map(|order| {
+                (0..count).filter(move |place| !order.contains(place)).map(move |place| {
+                    let mut extended = order.clone();
+                    extended.push(place);
+                    extended
+                })
+            })
+            .collect();
+    }
+    result
+}
+
+// An order of count places must contain every place 0..count once
+fn check_order(order: &[usize], count: usize) -> Result<(), String> {
+    let mut sorted = order.to_vec();
+    sorted.sort_unstable();
+    if sorted == (0..count).collect::<Vec<usize>>() {
+        Ok(())
+    } else {
+        Err(format!("Invalid order {:?}", order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Distinct;
+    use crate::games::*;
+    use crate::sudoku::Game;
+    use crate::thermometer::Thermometer;
+    use std::rc::Rc;
+
+    fn transformations() -> Vec<Transformation> {
+        vec![
+            Transformation::Relabel(vec![9, 8, 7, 6, 5, 4, 3, 2, 1]),
+            Transformation::PermuteBands(vec![2, 0, 1]),
+            Transformation::PermuteStacks(vec![1, 2, 0]),
+            Transformation::PermuteRows { band: 1, order: vec![2, 1, 0] },
+            Transformation::PermuteColumns { stack: 0, order: vec![1, 0, 2] },
+            Transformation::Transpose,
+            Transformation::Rotate,
+        ]
+    }
+
+    #[test]
+    fn test_transformations_move_squares() {
+        let board = Board::new("easy", easy_sudoku());
+        let moved = |transformation: Transformation, row, col| transformation.apply(&board).unwrap().square(row, col).value();
+        let value = board.square(1, 4).value();
+        assert_eq!(10 - value, moved(Transformation::Relabel(vec![9, 8, 7, 6, 5, 4, 3, 2, 1]), 1, 4));
+        assert_eq!(value, moved(Transformation::PermuteBands(vec![2, 0, 1]), 4, 4));
+        assert_eq!(value, moved(Transformation::PermuteStacks(vec![1, 2, 0]), 1, 1));
+        assert_eq!(value, moved(Transformation::PermuteColumns { stack: 1, order: vec![0, 2, 1] }, 1, 4));
+        assert_eq!(value, moved(Transformation::Transpose, 4, 1));
+        assert_eq!(value, moved(Transformation::Rotate, 4, 9));
+    }
+
+    #[test]
+    fn test_transformed_puzzle_has_transformed_solution() {
+        let solution = Board::new("solution", easy_sudoku_solution());
+        for transformation in transformations() {
+            let puzzle = transformation.apply(&Board::new("easy", easy_sudoku())).unwrap();
+            let expected = transformation.apply(&solution).unwrap();
+            assert!(expected.solved());
+            let mut game = Game::new("transformed", &puzzle.to_string());
+            assert!(game.solve());
+            assert_eq!(expected.to_string(), game.to_string());
+        }
+    }
+
+    #[test]
+    fn test_givens_move_along() {
+        let mut puzzle = Board::new("easy", easy_sudoku());
+        puzzle.minimize();
+        for transformation in transformations() {
+            assert!(transformation.apply(&puzzle).unwrap().is_minimal());
+        }
+    }
+
+    #[test]
+    fn test_candidates_are_transformed() {
+        let mut board = Board::new("candidates", "");
+        board.eliminate(1, 3);
+        let relabelled = Transformation::Relabel(vec![2, 3, 1, 4, 5, 6, 7, 8, 9]).apply(&board).unwrap();
+        assert_eq!(vec![2, 3, 4, 5, 6, 7, 8, 9], relabelled.square(1, 2).candidates());
+        let transposed = Transformation::Transpose.apply(&board).unwrap();
+        assert!(!transposed.square(2, 1).can_have_value(3));
+    }
+
+    #[test]
+    fn test_distinct_constraints_move_along() {
+        let mut board = Board::new("distinct", "1........");
+        board.add_constraint(Rc::new(Distinct::new(vec![0, 80])));
+        let mut rotated = Transformation::Rotate.apply(&board).unwrap();
+        rotated.solve();
+        // Square 0 ended up in the top right corner, square 80 in the bottom left corner
+        assert_eq!(1, rotated.square(1, 9).value());
+        assert!(!rotated.square(9, 1).can_have_value(1));
+    }
+
+    #[test]
+    fn test_invalid_transformations_are_rejected() {
+        let board = Board::new("easy", easy_sudoku());
+        let error = |transformation: Transformation| transformation.apply(&board).map(|_| ()).unwrap_err();
+        assert_eq!("Invalid order [0, 0, 1]", error(Transformation::PermuteBands(vec![0, 0, 1])));
+        assert_eq!("Invalid relabelling [1, 2]", error(Transformation::Relabel(vec![1, 2])));
+        assert_eq!("Band 3 is outside the grid", error(Transformation::PermuteRows { band: 3, order: vec![0, 1, 2] }));
+        let mut thermo = Board::new("thermo", "");
+        thermo.add_constraint(Rc::new(Thermometer::new(vec![0, 1, 2])));
+        assert_eq!(
+            Err("The constraints of the board can't be transformed".to_string()),
+            Transformation::Transpose.apply(&thermo).map(|_| ())
+        );
+        let six = Board::with_layout("6x6", "", Layout::new(2, 3));
+        assert_eq!(
+            Err("Only grids with square boxes can be transposed or rotated".to_string()),
+            Transformation::Rotate.apply(&six).map(|_| ())
+        );
+        let mut windoku = Layout::classic();
+        windoku.add_windows();
+        assert_eq!(
+            Err("Only grids with rows, columns and regular boxes can be transformed".to_string()),
+            Transformation::Transpose.apply(&Board::with_layout("windoku", "", windoku)).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_equivalent_puzzles_have_the_same_canonical_form() {
+        let puzzle = Board::new("easy", easy_sudoku());
+        let mut transformed = puzzle.clone();
+        for transformation in transformations() {
+            transformed = transformation.apply(&transformed).unwrap();
+        }
+        assert_ne!(puzzle.to_string(), transformed.to_string());
+        let canonical = canonical_form(&puzzle).unwrap();
+        assert_eq!(canonical, canonical_form(&transformed).unwrap());
+        assert_eq!(canonical, canonical_form(&Board::new("canonical", &canonical)).unwrap());
+        assert_eq!(Ok(true), is_equivalent(&puzzle, &transformed));
+        assert_eq!(Ok(false), is_equivalent(&puzzle, &Board::new("other", easy_sudoku2())));
+    }
+
+    #[test]
+    fn test_canonical_form_of_near_empty_puzzles() {
+        let empty = ".........\n".repeat(9);
+        assert_eq!(Ok(empty), canonical_form(&Board::new("empty", "")));
+        let single = canonical_form(&Board::new("single", "\n\n\n\n....7....")).unwrap();
+        assert_eq!(format!("1........\n{}", ".........\n".repeat(8)), single);
+        let two = Board::new("two", "........3\n\n\n\n....7....");
+        let relabelled = Transformation::Relabel(vec![2, 3, 4, 5, 6, 7, 8, 9, 1]).apply(&two).unwrap();
+        let moved = Transformation::Rotate.apply(&relabelled).unwrap();
+        assert_eq!(Ok(true), is_equivalent(&two, &moved));
+        assert_eq!(Ok(false), is_equivalent(&two, &Board::new("same row", "....7...3")));
+    }
+
+    #[test]
+    fn test_canonical_form_of_a_solution_starts_with_1_to_9() {
+        let canonical = canonical_form(&Board::new("solution", easy_sudoku_solution())).unwrap();
+        assert!(canonical.starts_with("123456789\n"));
+        assert!(Board::new("canonical", &canonical).solved());
+    }
+
+    #[test]
+    fn test_column_orders_keep_stacks_together() {
+        assert_eq!(1296, column_orders(&Layout::classic()).len());
+        assert_eq!(72, column_orders(&Layout::new(2, 3)).len());
+        assert!(column_orders(&Layout::new(2, 2)).contains(&vec![3, 2, 0, 1]));
+        assert!(!column_orders(&Layout::new(2, 2)).contains(&vec![0, 2, 1, 3]));
+    }
+}
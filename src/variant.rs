@@ -0,0 +1,186 @@
+// Text format for the extra constraints of a variant puzzle: one constraint per line, a keyword followed by a colon
+// and the squares it applies to, in "r<row>c<col>" notation. Lines are given from one end to the other. E.g.
+// even: r1c1 r1c3 r5c5
+// odd: r2c2
+// whisper: r3c1 r3c2 r4c3
+// renban: r7c7 r7c8 r8c9
+// palindrome: r2c5 r3c6 r4c7
+// between: r5c1 r6c2 r7c2 r8c1
+// A little killer clue gives the sum, the first square of the diagonal and the direction it points in
+// (down-right, down-left, up-right or up-left). The clue is outside the grid, so the first square is on an edge the
+// direction points in from, and the diagonal runs until it leaves the grid. E.g.
+// little killer: 23 r1c2 down-right
+// Empty lines are skipped
+//
+// Each line becomes a constraint that can be added to a Game or a Board
+
+use crate::constraint::Constraint;
+use crate::layout::Layout;
+use crate::lines::{Between, LittleKiller, Palindrome, Parity, Renban, Whisper};
+use std::rc::Rc;
+
+pub fn parse(constraints: &str, layout: &Layout) -> Result<Vec<Rc<dyn Constraint>>, String> {
+    let mut result: Vec<Rc<dyn Constraint>> = Vec::new();
+    for line in constraints.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+        let (keyword, squares) = match line.split_once(':') {
+            Some((keyword, squares)) => (keyword.trim(), squares),
+            None => return Err(format!("Unexpected line '{}'", line)),
+        };
+        if keyword == "little killer" {
+            result.push(Rc::new(parse_little_killer(line, squares, layout)?));
+            continue;
+        }
+        let squares = layout.parse_squares(squares)?;
+        if squares.is_empty() {
+            return Err(format!("No squares in '{}'", line));
+        }
+        match keyword {
+            "even" => result.push(Rc::new(Parity::new(squares, Vec::new()))),
+            "odd" => result.push(Rc::new(Parity::new(Vec::new(), squares))),
+            "whisper" => result.push(Rc::new(Whisper::german(parse_line(line, squares, layout)?, layout.size()))),
+            "renban" => {
+                if squares.len() > layout.size() {
+                    return Err(format!("Renban '{}' has more than {} squares", line, layout.size()));
+                }
+                result.push(Rc::new(Renban::new(parse_line(line, squares, layout)?)))
+            }
+            "palindrome" => result.push(Rc::new(Palindrome::new(parse_line(line, squares, layout)?))),
+            "between" => {
+                let between = Between::new(parse_line(line, squares, layout)?)
+                    .map_err(|error| format!("{} in '{}'", error, line))?;
+                result.push(Rc::new(between))
+            }
+            _ => return Err(format!("Unknown constraint '{}'", keyword)),
+        }
+    }
+    Ok(result)
+}
+
+// Lines need at least 2 touching squares
+fn parse_line(line: &str, squares: Vec<usize>, layout: &Layout) -> Result<Vec<usize>, String> {
+    if squares.len() < 2 {
+        return Err(format!("Line '{}' needs at least 2 squares", line));
+    }
+    match layout.gap_in(&squares) {
+        Some((pos, other)) => Err(format!(
+            "Line '{}' has a gap between {} and {}",
+            line,
+            layout.name_of(pos),
+            layout.name_of(other)
+        )),
+        None => Ok(squares),
+    }
+}
+
+// The clue is "<sum> <first square> <direction>"
+fn parse_little_killer(line: &str, clue: &str, layout: &Layout) -> Result<LittleKiller, String> {
+    let parts: Vec<&str> = clue.split_whitespace().collect();
+    if parts.len() != 3 {
+        return Err(format!("Little killer '{}' needs a sum, a square and a direction", line));
+    }
+    let sum = parts[0].parse::<usize>().map_err(|_| format!("Invalid little killer sum '{}'", parts[0]))?;
+    let first = layout.parse_square(parts[1])?;
+    let (row_step, col_step) = match parts[2] {
+        "down-right" => (1, 1),
+        "down-left" => (1, -1),
+        "up-right" => (-1, 1),
+        "up-left" => (-1, -1),
+        _ => return Err(format!("Unknown direction '{}'", parts[2])),
+    };
+    let size = layout.size() as isize;
+    let (mut row, mut col) = ((first / layout.size()) as isize, (first % layout.size()) as isize);
+    // The clue sits outside the grid, so the first square is on the edge the diagonal comes in from
+    let enters_row = if row_step > 0 { row == 0 } else { row == size - 1 };
+    let enters_col = if col_step > 0 { col == 0 } else { col == size - 1 };
+    if !enters_row && !enters_col {
+        return Err(format!("Little killer '{}' must start on the edge it points in from", line));
+    }
+    let mut squares = Vec::new();
+    while row >= 0 && row < size && col >= 0 && col < size {
+        squares.push((row * size + col) as usize);
+        row += row_step;
+        col += col_step;
+    }
+    Ok(LittleKiller::new(squares, sum))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Board;
+
+    #[test]
+    fn test_parse_constraints() {
+        let layout = Layout::classic();
+        let constraints = parse("\neven: r1c1 r1c3\nodd: r2c2\n\nwhisper: r3c1 r3c2\nrenban: r7c7 r8c8 r9c9\n", &layout).unwrap();
+        assert_eq!(4, constraints.len());
+
+        let mut board = Board::new("variant", "");
+        for constraint in constraints.iter() {
+            board.add_constraint(constraint.clone());
+        }
+        board.solve();
+        assert_eq!(vec![2, 4, 6, 8], board.square(1, 3).candidates());
+        assert_eq!(vec![1, 3, 5, 7, 9], board.square(2, 2).candidates());
+        assert!(!board.square(3, 1).can_have_value(5));
+    }
+
+    #[test]
+    fn test_parse_diagonal_and_line_constraints() {
+        let layout = Layout::classic();
+        let constraints = parse(
+            "little killer: 6 r7c1 down-right\nlittle killer: 3 r1c2 down-left\npalindrome: r1c5 r1c6\nbetween: r9c9 r8c9 r7c9",
+            &layout,
+        )
+        .unwrap();
+        assert_eq!(4, constraints.len());
+
+        let mut board = Board::new("variant", "........9");
+        for constraint in constraints.iter() {
+            board.add_constraint(constraint.clone());
+        }
+        board.solve();
+        // r7c1 r8c2 r9c3 add up to 6, so none of them is more than 6 - 1 - 1
+        assert_eq!(vec![1, 2, 3, 4], board.square(9, 3).candidates());
+        // r1c2 r2c1 add up to 3, and are in the same box
+        assert_eq!(vec![1, 2], board.square(1, 2).candidates());
+        assert_eq!(board.square(1, 5).candidates(), board.square(1, 6).candidates());
+        assert!(!board.square(8, 9).can_have_value(1));
+    }
+
+    #[test]
+    fn test_invalid_constraints_are_rejected() {
+        let layout = Layout::new(2, 2);
+        let error = |constraints| parse(constraints, &layout).map(|_| ()).unwrap_err();
+        assert_eq!("Unexpected line 'even r1c1'", error("even r1c1"));
+        assert_eq!("Unknown constraint 'prime'", error("prime: r1c1"));
+        assert_eq!("No squares in 'odd:'", error("odd:"));
+        assert_eq!("Invalid square 'r1'", error("odd: r1"));
+        assert_eq!("Line 'whisper: r1c1' needs at least 2 squares", error("whisper: r1c1"));
+        assert_eq!("Line 'renban: r1c1 r1c3' has a gap between r1c1 and r1c3", error("renban: r1c1 r1c3"));
+        assert_eq!(
+            "Between line needs at least 3 squares, found 2 in 'between: r1c1 r1c2'",
+            error("between: r1c1 r1c2")
+        );
+        assert_eq!(
+            "Little killer 'little killer: 5 r1c1' needs a sum, a square and a direction",
+            error("little killer: 5 r1c1")
+        );
+        assert_eq!("Invalid little killer sum 'x'", error("little killer: x r1c1 down-right"));
+        assert_eq!("Unknown direction 'down'", error("little killer: 5 r1c1 down"));
+        assert_eq!(
+            "Little killer 'little killer: 5 r2c2 down-right' must start on the edge it points in from",
+            error("little killer: 5 r2c2 down-right")
+        );
+        assert_eq!(
+            "Little killer 'little killer: 5 r1c2 up-left' must start on the edge it points in from",
+            error("little killer: 5 r1c2 up-left")
+        );
+        assert!(parse("little killer: 5 r1c4 down-left\nlittle killer: 5 r3c4 up-left", &layout).is_ok());
+        assert!(parse("little killer: 5 r4c2 up-right\nlittle killer: 5 r2c1 down-right", &layout).is_ok());
+        assert_eq!(
+            "Renban 'renban: r1c1 r1c2 r1c3 r1c4 r2c4' has more than 4 squares",
+            error("renban: r1c1 r1c2 r1c3 r1c4 r2c4")
+        );
+    }
+}
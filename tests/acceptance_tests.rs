@@ -1,5 +1,16 @@
+use rust_sudoku::adjacency::Adjacency;
+use rust_sudoku::arrow::Arrow;
+use rust_sudoku::constraint::Distinct;
 use rust_sudoku::games::*;
+use rust_sudoku::inequality::Inequality;
+use rust_sudoku::killer::Killer;
+use rust_sudoku::layout::Layout;
+use rust_sudoku::multi_grid::MultiGrid;
+use rust_sudoku::sandwich::Sandwich;
 use rust_sudoku::sudoku::Game;
+use rust_sudoku::thermometer::Thermometer;
+use rust_sudoku::variant;
+use std::rc::Rc;
 
 // Acceptance tests with increasingly difficult Sudokus to solve
 
@@ -72,3 +83,188 @@ fn test_can_solve_jovial_negative() {
     game.solve();
     assert_eq!(jovial_negative_solution(), game.to_string());
 }
+
+#[test]
+fn test_can_solve_kids_4x4() {
+    let mut game = Game::with_layout("kids 4x4", kids_4x4(), Layout::new(2, 2));
+    game.solve();
+    assert_eq!(kids_4x4_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_kids_6x6() {
+    let mut game = Game::with_layout("kids 6x6", kids_6x6(), Layout::new(2, 3));
+    game.solve();
+    assert_eq!(kids_6x6_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_numbers_12x12() {
+    let mut game = Game::with_layout("numbers 12x12", numbers_12x12(), Layout::new(3, 4));
+    game.solve();
+    assert_eq!(numbers_12x12_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_hardcore_16x16() {
+    let mut game = Game::with_layout("hardcore 16x16", hardcore_16x16(), Layout::new(4, 4));
+    game.solve();
+    assert_eq!(hardcore_16x16_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_hardcore_25x25() {
+    let mut game = Game::with_layout("hardcore 25x25", hardcore_25x25(), Layout::new(5, 5));
+    game.solve();
+    assert_eq!(hardcore_25x25_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_jigsaw_sudoku() {
+    let layout = Layout::jigsaw(jigsaw_regions()).unwrap();
+    let mut game = Game::with_layout("jigsaw", jigsaw_sudoku(), layout);
+    game.solve();
+    assert_eq!(jigsaw_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_x_sudoku() {
+    let mut layout = Layout::classic();
+    layout.add_diagonals();
+    let mut game = Game::with_layout("X-Sudoku", x_sudoku(), layout);
+    game.solve();
+    assert_eq!(x_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_windoku() {
+    let mut layout = Layout::classic();
+    layout.add_windows();
+    let mut game = Game::with_layout("Windoku", windoku(), layout);
+    game.solve();
+    assert_eq!(windoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_killer_sudoku_without_givens() {
+    let killer = Killer::parse(killer_cages(), &Layout::classic()).unwrap();
+    let mut game = Game::new("Killer", "");
+    game.add_constraint(Rc::new(killer));
+    game.solve();
+    assert_eq!(killer_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_thermo_sudoku() {
+    let mut game = Game::new("Thermo", thermo_sudoku());
+    for thermometer in Thermometer::parse(thermo_thermometers(), &Layout::classic()).unwrap() {
+        game.add_constraint(Rc::new(thermometer));
+    }
+    game.solve();
+    assert_eq!(thermo_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_arrow_sudoku() {
+    let mut game = Game::new("Arrow", arrow_sudoku());
+    for arrow in Arrow::parse(arrow_arrows(), &Layout::classic()).unwrap() {
+        game.add_constraint(Rc::new(arrow));
+    }
+    game.solve();
+    assert_eq!(arrow_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_kropki_sudoku_without_givens() {
+    let kropki = Adjacency::parse(kropki_dots(), &Layout::classic()).unwrap();
+    let mut game = Game::new("Kropki", "");
+    game.add_constraint(Rc::new(kropki));
+    game.solve();
+    assert_eq!(kropki_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_xv_sudoku() {
+    let xv = Adjacency::parse(xv_marks(), &Layout::classic()).unwrap();
+    let mut game = Game::new("XV", xv_sudoku());
+    game.add_constraint(Rc::new(xv));
+    game.solve();
+    assert_eq!(xv_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_anti_knight_sudoku() {
+    let mut layout = Layout::classic();
+    layout.add_anti_knight();
+    let mut game = Game::with_layout("Anti-knight", anti_knight_sudoku(), layout);
+    game.solve();
+    assert_eq!(anti_knight_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_anti_king_sudoku() {
+    let mut layout = Layout::classic();
+    layout.add_anti_king();
+    let mut game = Game::with_layout("Anti-king", anti_king_sudoku(), layout);
+    game.solve();
+    assert_eq!(anti_king_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_samurai_sudoku() {
+    let mut samurai = MultiGrid::samurai("Samurai", samurai_sudoku()).unwrap();
+    assert!(samurai.solve());
+    assert_eq!(samurai_sudoku_solution(), samurai.to_string());
+}
+
+#[test]
+fn test_can_solve_sandwich_sudoku() {
+    let sandwich = Sandwich::parse(sandwich_clues(), &Layout::classic()).unwrap();
+    let mut game = Game::new("Sandwich", sandwich_sudoku());
+    game.add_constraint(Rc::new(sandwich));
+    game.solve();
+    assert_eq!(sandwich_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_whisper_renban_and_parity_sudoku() {
+    let mut game = Game::new("Lines", lines_sudoku());
+    for constraint in variant::parse(lines_constraints(), &Layout::classic()).unwrap() {
+        game.add_constraint(constraint);
+    }
+    game.solve();
+    assert_eq!(lines_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_little_killer_palindrome_and_between_sudoku() {
+    let mut game = Game::new("Diagonals", diagonals_sudoku());
+    for constraint in variant::parse(diagonals_constraints(), &Layout::classic()).unwrap() {
+        game.add_constraint(constraint);
+    }
+    game.solve();
+    assert_eq!(diagonals_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_inequality_sudoku() {
+    let (initial, inequality) = Inequality::parse(inequality_sudoku(), &Layout::classic()).unwrap();
+    let mut game = Game::new("Inequality", &initial);
+    game.add_constraint(Rc::new(inequality));
+    game.solve();
+    assert_eq!(inequality_sudoku_solution(), game.to_string());
+}
+
+#[test]
+fn test_can_solve_killer_anti_knight_diagonal_sudoku() {
+    let mut layout = Layout::classic();
+    layout.add_anti_knight();
+    let killer = Killer::parse(combined_cages(), &layout).unwrap();
+    let mut game = Game::with_layout("Combined", combined_sudoku(), layout);
+    game.add_constraint(Rc::new(killer));
+    // The diagonals as constraints instead of extra units of the layout
+    game.add_constraint(Rc::new(Distinct::new((0..9).map(|i| i * 10).collect())));
+    game.add_constraint(Rc::new(Distinct::new((1..=9).map(|i| i * 8).collect())));
+    game.solve();
+    assert_eq!(combined_sudoku_solution(), game.to_string());
+}